@@ -0,0 +1,160 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{AddError, Task, TaskScheduler};
+
+/// Which OR-groups have been satisfied during one scheduling run
+#[derive(Debug)]
+pub(crate) struct OrGroups<'a> {
+    /// For every task named in an OR-group: the `(waiting task, group index)` pairs it is part of
+    members: HashMap<&'a str, Vec<(&'a str, usize)>>,
+    satisfied: HashSet<(&'a str, usize)>,
+}
+
+impl<'a> OrGroups<'a> {
+    pub(crate) fn new(tasks: &'a HashMap<String, Task>) -> Self {
+        let mut members: HashMap<&str, Vec<(&str, usize)>> = HashMap::new();
+        for task in tasks.values() {
+            for (index, group) in task.or_groups.iter().enumerate() {
+                for member in group {
+                    members.entry(member).or_default().push((&task.name, index));
+                }
+            }
+        }
+        OrGroups {
+            members,
+            satisfied: HashSet::new(),
+        }
+    }
+
+    /// `finished` is done: the tasks that had an OR-group satisfied by it, once per group.
+    /// Only the first member of a group to finish counts
+    pub(crate) fn finished(&mut self, finished: &str) -> Vec<&'a str> {
+        let Some(waiting) = self.members.get(finished) else {
+            return Vec::new();
+        };
+        waiting
+            .iter()
+            .filter(|&&group| self.satisfied.insert(group))
+            .map(|&(task, _)| task)
+            .collect()
+    }
+}
+
+impl TaskScheduler {
+    /// Add a task that waits for all of `and_deps`, and for at least one task out of each of
+    /// `or_groups`. Empty groups are dropped.
+    ///
+    /// The single-threaded and parallel schedulers honor OR-groups, a task whose groups can
+    /// never be satisfied is reported as a cycle. Other analyses (critical path, slack, levels
+    /// and so on) only see the `and_deps`
+    pub fn add_task_with_or_deps(
+        &mut self,
+        name: &str,
+        and_deps: Vec<&str>,
+        or_groups: Vec<Vec<&str>>,
+        duration: u32,
+    ) -> Result<(), AddError> {
+        if self.tasks.contains_key(name) {
+            return Err(AddError::DuplicateTask(name.to_string()));
+        }
+        self.insert_task(Task {
+            name: name.to_string(),
+            dependencies: and_deps.into_iter().map(str::to_string).collect(),
+            duration,
+            or_groups: or_groups
+                .into_iter()
+                .filter(|group| !group.is_empty())
+                .map(|group| group.into_iter().map(str::to_string).collect())
+                .collect(),
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// How many things each task waits for before it is ready:
+    /// one per dependency plus one per OR-group. An empty OR-group is already satisfied
+    pub(crate) fn ready_degrees(&self) -> HashMap<String, usize> {
+        let mut in_degree = self.first_level_dep.clone();
+        for task in self.tasks.values() {
+            let groups = task
+                .or_groups
+                .iter()
+                .filter(|group| !group.is_empty())
+                .count();
+            if groups > 0 {
+                *in_degree.entry(task.name.clone()).or_insert(0) += groups;
+            }
+        }
+        in_degree
+    }
+}
+
+#[test]
+fn test_or_dependencies() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("setup", vec![], 1);
+    scheduler.add_task("fast", vec![], 1);
+    scheduler.add_task("slow", vec![], 5);
+    scheduler
+        .add_task_with_or_deps("X", vec!["setup"], vec![vec!["fast", "slow"]], 2)
+        .unwrap();
+
+    // X only needs one of the alternatives, so it does not wait for the slow one
+    let schedule = scheduler.schedule_tasks_parallel(3).unwrap();
+    let x = schedule.iter().find(|(name, ..)| name == "X").unwrap();
+    assert_eq!(x.1, 1);
+
+    let single = scheduler.schedule_tasks().unwrap();
+    assert_eq!(single.len(), 4);
+    let position = |name: &str| single.iter().position(|(n, ..)| n == name).unwrap();
+    assert!(position("setup") < position("X"));
+    assert!(position("fast").min(position("slow")) < position("X"));
+}
+
+#[test]
+fn test_or_dependencies_cycle() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 1);
+    scheduler
+        .add_task_with_or_deps("B", vec![], vec![vec!["C"]], 1)
+        .unwrap();
+    scheduler.add_task("C", vec!["B"], 1);
+    assert_eq!(
+        scheduler.schedule_tasks(),
+        Err(crate::ScheduleError::CycleDetected)
+    );
+
+    // one way out of the cycle is enough
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 1);
+    scheduler
+        .add_task_with_or_deps("B", vec![], vec![vec!["C", "A"]], 1)
+        .unwrap();
+    scheduler.add_task("C", vec!["B"], 1);
+    assert_eq!(scheduler.schedule_tasks().unwrap().len(), 3);
+    assert_eq!(scheduler.schedule_tasks_parallel(2).unwrap().len(), 3);
+}
+
+#[test]
+fn test_remove_or_group_member() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("fast", vec![], 1);
+    scheduler.add_task("slow", vec![], 5);
+    scheduler
+        .add_task_with_or_deps("X", vec![], vec![vec!["fast", "slow"], vec!["slow"]], 2)
+        .unwrap();
+
+    scheduler.remove_node("slow");
+    assert_eq!(scheduler.tasks["X"].or_groups, [vec!["fast".to_string()]]);
+    scheduler.remove_node("fast");
+    assert!(scheduler.tasks["X"].or_groups.is_empty());
+    // nothing left to wait for, X is a plain root now
+    assert_eq!(
+        scheduler.schedule_tasks().unwrap(),
+        [("X".to_string(), 0, 2)]
+    );
+    assert_eq!(
+        scheduler.runner().unwrap().next_ready().as_deref(),
+        Some("X")
+    );
+}
@@ -0,0 +1,344 @@
+//! Compact binary encoding of a scheduler.
+//!
+//! Layout (all integers little endian):
+//! `b"MOTS"`, a version byte, the task count as `u32`, then per task, sorted by name:
+//! name, dependencies, duration, lags, weight, deadline, soft deadline, penalty per unit,
+//! priority, exclusion groups, resources, fixed start, concurrency group, max concurrent,
+//! setup, teardown, metadata, protected and divisible (one byte each), OR-groups (a list of
+//! lists of names), affinity,
+//! what the task was collapsed from, if anything: a presence byte, then the collapsed tasks
+//! and the dependents as they were, each encoded like a top-level task,
+//! and the success and failure branches (lists of names).
+//! The resource capacities come last.
+//! Strings are a `u32` byte length followed by UTF-8, lists are a `u32` length followed by items.
+//! The derived maps are not stored, they are rebuilt on load
+
+use std::collections::HashMap;
+
+use crate::{Collapsed, Task, TaskScheduler};
+
+const MAGIC: &[u8; 4] = b"MOTS";
+const VERSION: u8 = 1;
+
+/// Why a byte buffer could not be turned back into a scheduler
+#[derive(Debug, Eq, PartialEq)]
+pub enum LoadError {
+    /// Buffer does not start with the expected magic bytes
+    InvalidHeader,
+    /// Buffer was written by an unknown format version
+    UnsupportedVersion(u8),
+    /// Buffer ended in the middle of a value
+    UnexpectedEof,
+    /// A string was not valid UTF-8
+    InvalidUtf8,
+    /// The same task name appears twice
+    DuplicateTask(String),
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.u32(u32::try_from(len).expect("collection too large to encode"));
+    }
+
+    fn str(&mut self, value: &str) {
+        self.len(value.len());
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    /// Name to number map, sorted so the output is stable
+    fn pairs(&mut self, map: &HashMap<String, u32>) {
+        let mut pairs: Vec<(&String, &u32)> = map.iter().collect();
+        pairs.sort();
+        self.len(pairs.len());
+        for (name, value) in pairs {
+            self.str(name);
+            self.u32(*value);
+        }
+    }
+
+    /// Name to text map, sorted so the output is stable
+    fn str_pairs(&mut self, map: &HashMap<String, String>) {
+        let mut pairs: Vec<(&String, &String)> = map.iter().collect();
+        pairs.sort();
+        self.len(pairs.len());
+        for (key, value) in pairs {
+            self.str(key);
+            self.str(value);
+        }
+    }
+
+    fn option_u32(&mut self, value: Option<u32>) {
+        match value {
+            Some(value) => {
+                self.0.push(1);
+                self.u32(value);
+            }
+            None => self.0.push(0),
+        }
+    }
+
+    fn option_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.0.push(1);
+                self.str(value);
+            }
+            None => self.0.push(0),
+        }
+    }
+
+    fn task(&mut self, task: &Task) {
+        self.str(&task.name);
+        self.len(task.dependencies.len());
+        for dep in &task.dependencies {
+            self.str(dep);
+        }
+        self.u32(task.duration);
+
+        self.pairs(&task.lags);
+        self.u32(task.weight);
+        self.option_u32(task.deadline);
+        self.option_u32(task.soft_deadline);
+        self.u32(task.penalty_per_unit);
+        self.u32(task.priority);
+        self.len(task.exclusion_groups.len());
+        for group in &task.exclusion_groups {
+            self.str(group);
+        }
+        self.pairs(&task.resources);
+        self.option_u32(task.fixed_start);
+        self.option_str(task.concurrency_group.as_deref());
+        self.option_u32(task.max_concurrent);
+        self.u32(task.setup);
+        self.u32(task.teardown);
+        self.str_pairs(&task.metadata);
+        self.0.push(task.protected as u8);
+        self.0.push(task.divisible as u8);
+        self.len(task.or_groups.len());
+        for group in &task.or_groups {
+            self.len(group.len());
+            for member in group {
+                self.str(member);
+            }
+        }
+        self.option_str(task.affinity.as_deref());
+        match &task.collapsed {
+            Some(collapsed) => {
+                self.0.push(1);
+                self.tasks(&collapsed.tasks);
+                self.tasks(&collapsed.dependents);
+            }
+            None => self.0.push(0),
+        }
+        self.names(&task.on_success);
+        self.names(&task.on_failure);
+    }
+
+    fn names(&mut self, names: &[String]) {
+        self.len(names.len());
+        for name in names {
+            self.str(name);
+        }
+    }
+
+    fn tasks(&mut self, tasks: &[Task]) {
+        self.len(tasks.len());
+        for task in tasks {
+            self.task(task);
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes(&mut self, count: usize) -> Result<&[u8], LoadError> {
+        if self.0.len() < count {
+            return Err(LoadError::UnexpectedEof);
+        }
+        let (head, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, LoadError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, LoadError> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes(
+            bytes.try_into().expect("4 bytes were read"),
+        ))
+    }
+
+    fn str(&mut self) -> Result<String, LoadError> {
+        let len = self.u32()? as usize;
+        let bytes = self.bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| LoadError::InvalidUtf8)
+    }
+
+    fn pairs(&mut self) -> Result<HashMap<String, u32>, LoadError> {
+        let mut map = HashMap::new();
+        for _ in 0..self.u32()? {
+            let name = self.str()?;
+            map.insert(name, self.u32()?);
+        }
+        Ok(map)
+    }
+
+    fn str_pairs(&mut self) -> Result<HashMap<String, String>, LoadError> {
+        let mut map = HashMap::new();
+        for _ in 0..self.u32()? {
+            let key = self.str()?;
+            map.insert(key, self.str()?);
+        }
+        Ok(map)
+    }
+
+    fn option_u32(&mut self) -> Result<Option<u32>, LoadError> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => Ok(Some(self.u32()?)),
+        }
+    }
+
+    fn option_str(&mut self) -> Result<Option<String>, LoadError> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => Ok(Some(self.str()?)),
+        }
+    }
+
+    fn task(&mut self) -> Result<Task, LoadError> {
+        let name = self.str()?;
+        let mut dependencies = Vec::new();
+        for _ in 0..self.u32()? {
+            dependencies.push(self.str()?);
+        }
+        Ok(Task {
+            name,
+            dependencies,
+            duration: self.u32()?,
+            lags: self.pairs()?,
+            weight: self.u32()?,
+            deadline: self.option_u32()?,
+            soft_deadline: self.option_u32()?,
+            penalty_per_unit: self.u32()?,
+            priority: self.u32()?,
+            exclusion_groups: (0..self.u32()?)
+                .map(|_| self.str())
+                .collect::<Result<_, _>>()?,
+            resources: self.pairs()?,
+            fixed_start: self.option_u32()?,
+            concurrency_group: self.option_str()?,
+            max_concurrent: self.option_u32()?,
+            setup: self.u32()?,
+            teardown: self.u32()?,
+            metadata: self.str_pairs()?,
+            protected: self.u8()? != 0,
+            divisible: self.u8()? != 0,
+            or_groups: (0..self.u32()?)
+                .map(|_| (0..self.u32()?).map(|_| self.str()).collect())
+                .collect::<Result<_, _>>()?,
+            affinity: self.option_str()?,
+            collapsed: match self.u8()? {
+                0 => None,
+                _ => Some(Collapsed {
+                    tasks: self.tasks()?,
+                    dependents: self.tasks()?,
+                }),
+            },
+            on_success: self.names()?,
+            on_failure: self.names()?,
+        })
+    }
+
+    fn names(&mut self) -> Result<Vec<String>, LoadError> {
+        (0..self.u32()?).map(|_| self.str()).collect()
+    }
+
+    fn tasks(&mut self) -> Result<Vec<Task>, LoadError> {
+        (0..self.u32()?).map(|_| self.task()).collect()
+    }
+}
+
+impl TaskScheduler {
+    /// Encode the scheduler in the compact binary format, see `from_bytes`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer(Vec::new());
+        w.0.extend_from_slice(MAGIC);
+        w.0.push(VERSION);
+
+        let mut tasks: Vec<&Task> = self.tasks.values().collect();
+        tasks.sort_by(|a, b| a.name.cmp(&b.name));
+        w.len(tasks.len());
+        for task in tasks {
+            w.task(task);
+        }
+        w.pairs(&self.resource_capacities);
+        w.0
+    }
+
+    /// Decode a scheduler written by `to_bytes`, rebuilding `first_level_dep` and `dependents`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LoadError> {
+        let mut r = Reader(bytes);
+        if r.bytes(MAGIC.len()).map_err(|_| LoadError::InvalidHeader)? != MAGIC {
+            return Err(LoadError::InvalidHeader);
+        }
+        match r.u8()? {
+            VERSION => {}
+            other => return Err(LoadError::UnsupportedVersion(other)),
+        }
+
+        let mut scheduler = TaskScheduler::new();
+        for _ in 0..r.u32()? {
+            let task = r.task()?;
+            if scheduler.tasks.contains_key(&task.name) {
+                return Err(LoadError::DuplicateTask(task.name));
+            }
+            scheduler.insert_task(task);
+        }
+        scheduler.resource_capacities = r.pairs()?;
+        Ok(scheduler)
+    }
+}
+
+#[test]
+fn test_bytes_round_trip() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler
+        .add_task_with_lags("E", vec![("D", 2)], 5)
+        .unwrap();
+    scheduler.set_deadline("E", Some(20)).unwrap();
+    scheduler.set_weight("B", 3).unwrap();
+    scheduler.add_exclusion_group("io", &["B", "C"]).unwrap();
+    scheduler.set_resource_capacity("gpu", 1);
+    scheduler.require_resource("D", "gpu", 1).unwrap();
+    scheduler.set_branches("C", vec![], vec!["E"]).unwrap();
+
+    let bytes = scheduler.to_bytes();
+    let loaded = TaskScheduler::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded, scheduler);
+    assert_eq!(loaded.schedule_tasks(), scheduler.schedule_tasks());
+}
+
+#[test]
+fn test_from_bytes_rejects_garbage() {
+    assert_eq!(
+        TaskScheduler::from_bytes(b"nope"),
+        Err(LoadError::InvalidHeader)
+    );
+
+    let bytes = crate::example_scheduler().to_bytes();
+    assert_eq!(
+        TaskScheduler::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(LoadError::UnexpectedEof)
+    );
+}
@@ -0,0 +1,102 @@
+use crate::{OverflowMode, ScheduleError, TaskScheduler};
+
+/// Time arithmetic used by the single-threaded schedule.
+///
+/// Task durations, lags, setup/teardown and pinned starts stay `u32` units of work,
+/// the clock decides where on its own timeline an instant ends up after that much work.
+/// A business-days clock, for example, can jump over weekends
+pub trait Clock {
+    type Instant: Copy + Ord;
+    type Duration: From<u32>;
+
+    /// When the schedule begins, pinned starts are measured from here
+    fn origin(&self) -> Self::Instant;
+
+    /// `from` moved on by `by`, or `None` if the result can't be represented
+    fn advance(&self, from: Self::Instant, by: Self::Duration) -> Option<Self::Instant>;
+}
+
+/// Plain `u32` time starting at 0, what `schedule_tasks` uses.
+/// Running past `u32::MAX` is a `DurationOverflow`
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct U32Clock;
+
+impl Clock for U32Clock {
+    type Instant = u32;
+    type Duration = u32;
+
+    fn origin(&self) -> u32 {
+        0
+    }
+
+    fn advance(&self, from: u32, by: u32) -> Option<u32> {
+        from.checked_add(by)
+    }
+}
+
+/// `u32` time starting at 0 that overflows as the mode says
+impl Clock for OverflowMode {
+    type Instant = u32;
+    type Duration = u32;
+
+    fn origin(&self) -> u32 {
+        0
+    }
+
+    fn advance(&self, from: u32, by: u32) -> Option<u32> {
+        self.add(from, by).ok()
+    }
+}
+
+impl TaskScheduler {
+    /// `schedule_tasks` with the times computed by `clock`, as `(name, start, duration)`.
+    /// The order is the same whatever the clock, `schedule_tasks` is this with `U32Clock`
+    pub fn schedule_tasks_with_clock<C: Clock>(
+        &self,
+        clock: &C,
+    ) -> Result<Vec<(String, C::Instant, u32)>, ScheduleError> {
+        self.schedule_single(clock, |_| 0, &mut crate::visitor::NoopVisitor, &[])
+    }
+}
+
+#[test]
+fn test_schedule_tasks_with_clock() {
+    /// Work doesn't progress during `[5, 7)`, time spent there is skipped
+    struct Maintenance;
+
+    impl Clock for Maintenance {
+        type Instant = u32;
+        type Duration = u32;
+
+        fn origin(&self) -> u32 {
+            0
+        }
+
+        fn advance(&self, from: u32, by: u32) -> Option<u32> {
+            let from = if (5..7).contains(&from) { 7 } else { from };
+            let end = from.checked_add(by)?;
+            if from < 5 && end > 5 {
+                end.checked_add(2)
+            } else {
+                Some(end)
+            }
+        }
+    }
+
+    let scheduler = crate::example_scheduler();
+    assert_eq!(
+        scheduler.schedule_tasks_with_clock(&U32Clock).unwrap(),
+        scheduler.schedule_tasks().unwrap()
+    );
+
+    // B finishes right as maintenance begins, so C waits until it is over
+    assert_eq!(
+        scheduler.schedule_tasks_with_clock(&Maintenance).unwrap(),
+        vec![
+            ("A".to_string(), 0, 3),
+            ("B".to_string(), 3, 2),
+            ("C".to_string(), 7, 1),
+            ("D".to_string(), 8, 4),
+        ]
+    );
+}
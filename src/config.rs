@@ -0,0 +1,51 @@
+use crate::ScheduleError;
+
+/// What to do when schedule time would run past `u32::MAX`
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum OverflowMode {
+    /// Fail with `ScheduleError::DurationOverflow`
+    #[default]
+    Error,
+    /// Clamp at `u32::MAX`: every task after that point starts at `u32::MAX`.
+    /// Reported durations are kept, so `start + duration` may not fit in a `u32`
+    Saturate,
+}
+
+impl OverflowMode {
+    /// `a + b` under this mode
+    pub(crate) fn add(self, a: u32, b: u32) -> Result<u32, ScheduleError> {
+        match self {
+            OverflowMode::Error => a.checked_add(b).ok_or(ScheduleError::DurationOverflow),
+            OverflowMode::Saturate => Ok(a.saturating_add(b)),
+        }
+    }
+}
+
+/// How a scaled duration is turned back into whole time units, see `schedule_with_factor_rounded`
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum RoundingMode {
+    /// Round down, which can underestimate the makespan
+    Floor,
+    /// Round up, the conservative choice
+    Ceil,
+    /// Round to the closest, half to even so rounding does not drift in one direction
+    #[default]
+    Nearest,
+}
+
+impl RoundingMode {
+    /// `value` rounded under this mode
+    pub(crate) fn round(self, value: f64) -> f64 {
+        match self {
+            RoundingMode::Floor => value.floor(),
+            RoundingMode::Ceil => value.ceil(),
+            RoundingMode::Nearest => value.round_ties_even(),
+        }
+    }
+}
+
+/// Knobs for `schedule_with_config`
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ScheduleConfig {
+    pub overflow: OverflowMode,
+}
@@ -0,0 +1,641 @@
+//! Critical path method: timings assuming every ready task can start right away,
+//! i.e as if there were unlimited workers
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{sort_by_start, ScheduleError, ScheduledTask, Task, TaskScheduler};
+
+/// Structured schedule out of a start time map, ordered by start and then name
+fn to_schedule(scheduler: &TaskScheduler, starts: &HashMap<String, u32>) -> Vec<ScheduledTask> {
+    let mut schedule: Vec<ScheduledTask> = starts
+        .iter()
+        .map(|(name, &start)| ScheduledTask {
+            name: name.clone(),
+            start,
+            duration: scheduler.tasks[name].duration,
+        })
+        .collect();
+    sort_by_start(&mut schedule);
+    schedule
+}
+
+impl TaskScheduler {
+    /// Earliest start of every task: the latest `finish + lag` among its dependencies,
+    /// or its pinned start time.
+    /// Fails with `DurationOverflow` if a task would finish past `u32::MAX`
+    pub(crate) fn earliest_starts(&self) -> Result<HashMap<String, u32>, ScheduleError> {
+        let mut earliest: HashMap<String, u32> = HashMap::new();
+        for name in self.topological_order()? {
+            let task = &self.tasks[&name];
+            let mut start = 0;
+            for dep in &task.dependencies {
+                let Some(dep_start) = earliest.get(dep) else {
+                    continue;
+                };
+                // the dependency's own finish was checked when it was placed
+                let ready = (dep_start + self.tasks[dep].duration)
+                    .checked_add(task.lag(dep))
+                    .ok_or(ScheduleError::DurationOverflow)?;
+                start = start.max(ready);
+            }
+            let start = match task.fixed_start {
+                Some(fixed) if fixed < start => {
+                    return Err(ScheduleError::ConstraintViolation(name));
+                }
+                Some(fixed) => fixed,
+                None => start,
+            };
+            if start.checked_add(task.duration).is_none() {
+                return Err(ScheduleError::DurationOverflow);
+            }
+            earliest.insert(name, start);
+        }
+        Ok(earliest)
+    }
+
+    /// Latest start of every task that still keeps the earliest possible makespan.
+    /// Computed with a backward pass over `dependents`, leaves finish exactly at the makespan
+    pub(crate) fn latest_starts(&self) -> Result<HashMap<String, u32>, ScheduleError> {
+        let earliest = self.earliest_starts()?;
+        let makespan = earliest
+            .iter()
+            .map(|(name, start)| start + self.tasks[name].duration)
+            .max()
+            .unwrap_or(0);
+
+        let mut latest: HashMap<String, u32> = HashMap::new();
+        for name in self.topological_order()?.into_iter().rev() {
+            // must be done by the time the most urgent dependent has to start
+            let finish_by = self
+                .dependents
+                .get(&name)
+                .into_iter()
+                .flatten()
+                .filter_map(|dependent| {
+                    let dependent_start = latest.get(dependent)?;
+                    Some(dependent_start.saturating_sub(self.tasks[dependent].lag(&name)))
+                })
+                .min()
+                .unwrap_or(makespan);
+            latest.insert(
+                name.clone(),
+                finish_by.saturating_sub(self.tasks[&name].duration),
+            );
+        }
+        Ok(latest)
+    }
+
+    /// Total slack (float) of every task: how far it can start later than its earliest start
+    /// without pushing out the makespan. Tasks on the critical path have zero slack
+    pub fn slack_analysis(&self) -> Result<HashMap<String, u32>, ScheduleError> {
+        let earliest = self.earliest_starts()?;
+        let latest = self.latest_starts()?;
+        Ok(earliest
+            .into_iter()
+            .map(|(name, start)| {
+                let slack = latest[&name] - start;
+                (name, slack)
+            })
+            .collect())
+    }
+
+    /// Free slack of every task: how far it can start later than its earliest start without
+    /// delaying the earliest start of any dependent (lags included), or for a leaf the makespan.
+    /// Never more than the total slack of `slack_analysis`, which may use up slack that
+    /// dependents share
+    pub fn free_slack(&self) -> Result<HashMap<String, u32>, ScheduleError> {
+        let earliest = self.earliest_starts()?;
+        let finish = |name: &String| earliest[name] + self.tasks[name].duration;
+        let makespan = earliest.keys().map(finish).max().unwrap_or(0);
+        Ok(earliest
+            .keys()
+            .map(|name| {
+                let free_until = self
+                    .dependents
+                    .get(name)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|dependent| {
+                        let start = earliest.get(dependent)?;
+                        Some(start.saturating_sub(self.tasks[dependent].lag(name)))
+                    })
+                    .min()
+                    .unwrap_or(makespan);
+                (name.clone(), free_until.saturating_sub(finish(name)))
+            })
+            .collect())
+    }
+
+    /// Tasks whose latest finish (latest start plus duration) is at or before `deadline`, sorted.
+    /// These must be done by `deadline`, or the earliest possible makespan slips
+    pub fn tasks_before_deadline(&self, deadline: u32) -> Result<Vec<String>, ScheduleError> {
+        let mut names: Vec<String> = self
+            .latest_starts()?
+            .into_iter()
+            .filter(|(name, start)| start + self.tasks[name].duration <= deadline)
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Earliest possible makespan (as in `schedule_asap`) if a task `name` were added
+    /// with `deps` and `duration`. `self` is left untouched.
+    ///
+    /// Every dependency must exist, and an existing name is a `DuplicateTask`.
+    /// Existing tasks may already name `name` as a dependency, so the new task can close a
+    /// cycle through them; that, like a cycle already in the graph, is `CycleDetected`
+    pub fn preview_add(
+        &self,
+        name: &str,
+        deps: Vec<&str>,
+        duration: u32,
+    ) -> Result<u32, ScheduleError> {
+        if self.tasks.contains_key(name) {
+            return Err(ScheduleError::DuplicateTask(name.to_string()));
+        }
+        if deps.iter().any(|dep| !self.tasks.contains_key(*dep)) {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        let mut preview = self.detached_copy();
+        preview.add_task(name, deps, duration);
+        Ok(preview
+            .earliest_starts()?
+            .iter()
+            .map(|(name, start)| start + preview.tasks[name].duration)
+            .max()
+            .unwrap_or(0))
+    }
+
+    /// Every task with its slack, most slack first and then by name.
+    /// The tasks at the front are the ones that can most safely wait
+    pub fn tasks_by_slack_desc(&self) -> Result<Vec<(String, u32)>, ScheduleError> {
+        let mut tasks: Vec<(String, u32)> = self.slack_analysis()?.into_iter().collect();
+        tasks.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(tasks)
+    }
+
+    /// Leaf tasks with positive slack, sorted.
+    ///
+    /// Only tasks without dependents qualify: delaying a non-leaf task shifts its dependents
+    /// and eats into their slack, while a leaf's delay has nowhere to cascade.
+    /// A leaf can of course still push out the makespan if delayed past its own slack
+    pub fn flexible_tasks(&self) -> Result<Vec<String>, ScheduleError> {
+        let slack = self.slack_analysis()?;
+        Ok(self
+            .leaves()
+            .into_iter()
+            .filter(|name| slack[name] > 0)
+            .collect())
+    }
+
+    /// Longest chain of tasks, from a root to the task finishing last.
+    ///
+    /// Walks back from the last finisher through the dependency that determined each start.
+    /// Ties go to the smaller name. Empty for an empty scheduler
+    pub fn critical_path(&self) -> Result<Vec<String>, ScheduleError> {
+        let earliest = self.earliest_starts()?;
+        let finish = |name: &String| earliest[name] + self.tasks[name].duration;
+
+        // `max_by_key` keeps the last maximum, so walk the names in reverse
+        let mut names: Vec<&String> = earliest.keys().collect();
+        names.sort();
+        let Some(mut current) = names.into_iter().rev().max_by_key(|name| finish(name)) else {
+            return Ok(Vec::new());
+        };
+
+        let mut path = vec![current.clone()];
+        loop {
+            let task = &self.tasks[current];
+            let mut deps: Vec<&String> = task.dependencies.iter().collect();
+            deps.sort();
+            let Some(dep) = deps
+                .into_iter()
+                .find(|dep| finish(dep) + task.lag(dep) == earliest[current])
+            else {
+                break;
+            };
+            path.push(dep.clone());
+            current = dep;
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    /// The task after `name` on `critical_path`, `None` if `name` is the last one.
+    /// `NoTaskFound` if `name` is not on the critical path at all
+    pub fn critical_successor(&self, name: &str) -> Result<Option<String>, ScheduleError> {
+        let path = self.critical_path()?;
+        let index = path
+            .iter()
+            .position(|task| task == name)
+            .ok_or(ScheduleError::NoTaskFound)?;
+        Ok(path.into_iter().nth(index + 1))
+    }
+
+    /// Number of distinct root-to-leaf paths as long as the longest one (durations plus lags).
+    /// More than one means no single task on them can be shortened to finish sooner.
+    /// Counted over the topological order without listing the paths, 0 for an empty scheduler.
+    /// A path longer than `u32::MAX` is a `DurationOverflow`
+    pub fn critical_path_count(&self) -> Result<usize, ScheduleError> {
+        // longest path ending at each task, and how many paths reach that length
+        let mut longest: HashMap<String, (u32, usize)> = HashMap::new();
+        for name in self.topological_order()? {
+            let task = &self.tasks[&name];
+            // a root starts the only path to itself
+            let mut best: Option<(u32, usize)> = None;
+            for dep in &task.dependencies {
+                let Some(&(length, count)) = longest.get(dep) else {
+                    continue;
+                };
+                let length = length
+                    .checked_add(task.lag(dep))
+                    .ok_or(ScheduleError::DurationOverflow)?;
+                best = match best {
+                    Some((top, paths)) if length == top => Some((top, paths.saturating_add(count))),
+                    Some((top, _)) if length < top => best,
+                    _ => Some((length, count)),
+                };
+            }
+            let (length, paths) = best.unwrap_or((0, 1));
+            let length = length
+                .checked_add(task.duration)
+                .ok_or(ScheduleError::DurationOverflow)?;
+            longest.insert(name, (length, paths));
+        }
+
+        let leaves: Vec<(u32, usize)> = longest
+            .iter()
+            .filter(|(name, _)| !self.dependents.contains_key(*name))
+            .map(|(_, &entry)| entry)
+            .collect();
+        let Some(critical) = leaves.iter().map(|&(length, _)| length).max() else {
+            return Ok(0);
+        };
+        Ok(leaves
+            .into_iter()
+            .filter(|&(length, _)| length == critical)
+            .fold(0, |total, (_, count)| total.saturating_add(count)))
+    }
+
+    /// How much to take off each task of the critical path, in path order, to bring the
+    /// `schedule_asap` makespan down to `target`. The cut is shared out in proportion to
+    /// the durations, tasks left untouched are omitted. Empty if `target` is already met.
+    ///
+    /// Only the critical path is looked at: another path may be critical once it is shorter,
+    /// so check again after applying. Lags can't be cut, if they alone exceed `target`
+    /// every task is cut to nothing and it still isn't met
+    pub fn rebalance_suggestions(&self, target: u32) -> Result<Vec<(String, u32)>, ScheduleError> {
+        let makespan = self
+            .schedule_asap()?
+            .iter()
+            .map(ScheduledTask::finish)
+            .max()
+            .unwrap_or(0);
+        let path = self.critical_path()?;
+        let durations: Vec<u64> = path
+            .iter()
+            .map(|name| u64::from(self.tasks[name].duration))
+            .collect();
+        let total: u64 = durations.iter().sum();
+        let needed = u64::from(makespan.saturating_sub(target)).min(total);
+        if needed == 0 {
+            return Ok(Vec::new());
+        }
+
+        // round every share down, then hand what is left to the largest remainders
+        let mut cuts: Vec<u64> = durations.iter().map(|d| needed * d / total).collect();
+        let mut by_remainder: Vec<usize> = (0..path.len()).collect();
+        by_remainder.sort_by_key(|&i| std::cmp::Reverse(needed * durations[i] % total));
+        let left = needed - cuts.iter().sum::<u64>();
+        for &i in by_remainder.iter().take(left as usize) {
+            cuts[i] += 1;
+        }
+        Ok(path
+            .into_iter()
+            .zip(cuts)
+            .filter(|&(_, cut)| cut > 0)
+            .map(|(name, cut)| (name, cut as u32))
+            .collect())
+    }
+
+    /// Root-to-leaf paths at most `within` shorter than the longest one, with their lengths
+    /// (durations plus lags along the path). Longest first, then by path.
+    ///
+    /// These are the paths that turn critical if the critical path gets shorter.
+    /// Enumerates paths with `all_paths_to`, so it shares its `MAX_PATHS` cap per leaf.
+    /// A path longer than `u32::MAX` is a `DurationOverflow`
+    pub fn near_critical_paths(
+        &self,
+        within: u32,
+    ) -> Result<Vec<(Vec<String>, u32)>, ScheduleError> {
+        self.topological_order()?;
+        let mut paths: Vec<(Vec<String>, u32)> = Vec::new();
+        for leaf in self.leaves() {
+            for path in self.all_paths_to(&leaf)? {
+                let mut length: u32 = 0;
+                for (i, name) in path.iter().enumerate() {
+                    let task = &self.tasks[name];
+                    let lag = if i > 0 { task.lag(&path[i - 1]) } else { 0 };
+                    length = length
+                        .checked_add(task.duration)
+                        .and_then(|length| length.checked_add(lag))
+                        .ok_or(ScheduleError::DurationOverflow)?;
+                }
+                paths.push((path, length));
+            }
+        }
+
+        let longest = paths.iter().map(|(_, length)| *length).max().unwrap_or(0);
+        paths.retain(|(_, length)| longest - length <= within);
+        paths.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(paths)
+    }
+
+    /// The critical path on its own: a new scheduler holding just those tasks,
+    /// each depending on the one before it (keeping the lag of that edge)
+    pub fn critical_path_subgraph(&self) -> Result<TaskScheduler, ScheduleError> {
+        let mut subgraph = TaskScheduler::new();
+        let mut previous: Option<String> = None;
+        for name in self.critical_path()? {
+            let task = &self.tasks[&name];
+            let dependencies: Vec<String> = previous.iter().cloned().collect();
+            let lags = dependencies
+                .iter()
+                .filter_map(|dep| Some((dep.clone(), *task.lags.get(dep)?)))
+                .collect();
+            subgraph.insert_task(Task {
+                dependencies,
+                lags,
+                ..task.clone()
+            });
+            previous = Some(name);
+        }
+        Ok(subgraph)
+    }
+
+    /// As-soon-as-possible schedule with unlimited workers, ordered by start time
+    pub fn schedule_asap(&self) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        Ok(to_schedule(self, &self.earliest_starts()?))
+    }
+
+    /// As-late-as-possible schedule with unlimited workers, ordered by start time.
+    /// Each task starts as late as it can without pushing out the makespan of `schedule_asap`,
+    /// so tasks with slack start later than in the early schedule
+    pub fn schedule_alap(&self) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        Ok(to_schedule(self, &self.latest_starts()?))
+    }
+
+    /// Re-plan while work is under way, with unlimited workers like `schedule_asap`.
+    ///
+    /// `in_progress` maps tasks that already started to their remaining duration: they run
+    /// from 0 for that long. Everything they depend on is done and left out of the schedule,
+    /// the rest waits for the remaining time as usual. Unknown names are `NoTaskFound`
+    pub fn schedule_with_progress(
+        &self,
+        in_progress: &HashMap<String, u32>,
+    ) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let mut done: HashSet<String> = HashSet::new();
+        for name in in_progress.keys() {
+            done.extend(self.all_dependencies(name)?);
+        }
+
+        let mut replanned = self.detached_copy();
+        for name in done.iter().filter(|name| !in_progress.contains_key(*name)) {
+            replanned.remove_node(name);
+        }
+        for (name, &remaining) in in_progress {
+            replanned.set_dependencies(name, vec![])?;
+            let task = replanned.tasks.get_mut(name).expect("checked above");
+            task.duration = remaining;
+            task.fixed_start = None;
+        }
+        replanned.schedule_asap()
+    }
+}
+
+#[test]
+fn test_schedule_alap() {
+    let scheduler = crate::example_scheduler();
+    let start_of = |schedule: &[ScheduledTask], name: &str| {
+        schedule.iter().find(|t| t.name == name).unwrap().start
+    };
+
+    let early = scheduler.schedule_asap().unwrap();
+    let late = scheduler.schedule_alap().unwrap();
+
+    // C only has to be done when D starts at 5
+    assert_eq!(start_of(&early, "C"), 3);
+    assert_eq!(start_of(&late, "C"), 4);
+
+    // tasks on the critical path can't move
+    for name in ["A", "B", "D"] {
+        assert_eq!(start_of(&early, name), start_of(&late, name));
+    }
+}
+
+#[test]
+fn test_slack_and_flexible_tasks() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.add_task("E", vec!["A"], 1);
+
+    let slack = scheduler.slack_analysis().unwrap();
+    assert_eq!(slack["A"], 0);
+    assert_eq!(slack["C"], 1);
+    assert_eq!(slack["E"], 5);
+
+    // C has slack too, but it feeds D so only the leaf E is flexible
+    assert_eq!(scheduler.flexible_tasks(), Ok(vec!["E".to_string()]));
+}
+
+#[test]
+fn test_critical_path() {
+    let scheduler = crate::example_scheduler();
+    assert_eq!(
+        scheduler.critical_path(),
+        Ok(vec!["A".into(), "B".into(), "D".into()])
+    );
+    assert_eq!(TaskScheduler::new().critical_path(), Ok(vec![]));
+
+    let subgraph = scheduler.critical_path_subgraph().unwrap();
+    assert_eq!(subgraph.tasks.len(), 3);
+    assert_eq!(subgraph.tasks["D"].dependencies, vec!["B"]);
+    let schedule = subgraph.schedule_tasks().unwrap();
+    assert_eq!(crate::parallel::makespan_of(&schedule), 9);
+}
+
+#[test]
+fn test_tasks_before_deadline() {
+    let scheduler = crate::example_scheduler();
+    // latest finishes: A 3, B 5, C 5, D 9
+    assert_eq!(
+        scheduler.tasks_before_deadline(5),
+        Ok(vec!["A".into(), "B".into(), "C".into()])
+    );
+    assert_eq!(scheduler.tasks_before_deadline(4), Ok(vec!["A".into()]));
+    assert_eq!(scheduler.tasks_before_deadline(9).unwrap().len(), 4);
+}
+
+#[test]
+fn test_preview_add() {
+    let scheduler = crate::example_scheduler();
+    // after D, on the critical path: 9 + 4
+    assert_eq!(scheduler.preview_add("E", vec!["D"], 4), Ok(13));
+    // after C, which has slack: C ends at 4 so F fits before D does
+    assert_eq!(scheduler.preview_add("F", vec!["C"], 1), Ok(9));
+    assert!(!scheduler.tasks.contains_key("E"));
+
+    assert_eq!(
+        scheduler.preview_add("A", vec![], 1),
+        Err(ScheduleError::DuplicateTask("A".into()))
+    );
+    assert_eq!(
+        scheduler.preview_add("E", vec!["missing"], 1),
+        Err(ScheduleError::NoTaskFound)
+    );
+
+    // A already waits on N, so N waiting on A closes a cycle
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec!["N"], 2);
+    assert_eq!(
+        scheduler.preview_add("N", vec!["A"], 1),
+        Err(ScheduleError::CycleDetected)
+    );
+    // while a plain N completes the graph
+    assert_eq!(scheduler.preview_add("N", vec![], 3), Ok(5));
+}
+
+#[test]
+fn test_tasks_by_slack_desc() {
+    assert_eq!(
+        crate::example_scheduler().tasks_by_slack_desc(),
+        Ok(vec![
+            ("C".to_string(), 1),
+            ("A".to_string(), 0),
+            ("B".to_string(), 0),
+            ("D".to_string(), 0),
+        ])
+    );
+}
+
+#[test]
+fn test_near_critical_paths() {
+    let scheduler = crate::example_scheduler();
+    let path = |names: &[&str]| -> Vec<String> { names.iter().map(|n| n.to_string()).collect() };
+    assert_eq!(
+        scheduler.near_critical_paths(2),
+        Ok(vec![
+            (path(&["A", "B", "D"]), 9),
+            (path(&["A", "C", "D"]), 8)
+        ])
+    );
+    assert_eq!(
+        scheduler.near_critical_paths(0),
+        Ok(vec![(path(&["A", "B", "D"]), 9)])
+    );
+    assert_eq!(scheduler.near_critical_paths(u32::MAX).unwrap().len(), 2);
+
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], u32::MAX);
+    scheduler.add_task("B", vec!["A"], 1);
+    assert_eq!(
+        scheduler.near_critical_paths(0),
+        Err(ScheduleError::DurationOverflow)
+    );
+}
+
+#[test]
+fn test_schedule_with_progress() {
+    let scheduler = crate::example_scheduler();
+    let in_progress = HashMap::from([("A".to_string(), 1)]);
+    let schedule = scheduler.schedule_with_progress(&in_progress).unwrap();
+
+    let start_of = |name: &str| schedule.iter().find(|t| t.name == name).unwrap().start;
+    assert_eq!(schedule[0].duration, 1);
+    // A only has 1 unit left, so B and C no longer wait until 3
+    assert_eq!(start_of("B"), 1);
+    assert_eq!(start_of("C"), 1);
+    assert_eq!(start_of("D"), 3);
+
+    // D can only have started once everything before it was done
+    let in_progress = HashMap::from([("D".to_string(), 2)]);
+    let schedule = scheduler.schedule_with_progress(&in_progress).unwrap();
+    assert_eq!(schedule.len(), 1);
+}
+
+#[test]
+fn test_critical_path_count() {
+    let mut scheduler = crate::example_scheduler();
+    // only A-B-D is 9 long
+    assert_eq!(scheduler.critical_path_count(), Ok(1));
+
+    scheduler.set_duration("C", 2).unwrap();
+    assert_eq!(scheduler.critical_path_count(), Ok(2));
+
+    assert_eq!(TaskScheduler::new().critical_path_count(), Ok(0));
+
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], u32::MAX);
+    scheduler.add_task("B", vec!["A"], 1);
+    assert_eq!(
+        scheduler.critical_path_count(),
+        Err(ScheduleError::DurationOverflow)
+    );
+}
+
+#[test]
+fn test_rebalance_suggestions() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 4);
+    scheduler.add_task("B", vec!["A"], 6);
+    scheduler.add_task("side", vec![], 3);
+
+    // 2 to cut, 0.8 of it from A and 1.2 from B
+    let cuts = scheduler.rebalance_suggestions(8).unwrap();
+    assert_eq!(cuts, vec![("A".to_string(), 1), ("B".to_string(), 1)]);
+    for (name, cut) in cuts {
+        let duration = scheduler.tasks[&name].duration;
+        scheduler.set_duration(&name, duration - cut).unwrap();
+    }
+    let makespan = scheduler
+        .schedule_asap()
+        .unwrap()
+        .iter()
+        .map(ScheduledTask::finish)
+        .max();
+    assert_eq!(makespan, Some(8));
+
+    assert_eq!(scheduler.rebalance_suggestions(8), Ok(vec![]));
+    assert_eq!(scheduler.rebalance_suggestions(20), Ok(vec![]));
+}
+
+#[test]
+fn test_critical_successor() {
+    let scheduler = crate::example_scheduler();
+    assert_eq!(scheduler.critical_successor("A"), Ok(Some("B".to_string())));
+    assert_eq!(scheduler.critical_successor("B"), Ok(Some("D".to_string())));
+    assert_eq!(scheduler.critical_successor("D"), Ok(None));
+    assert_eq!(
+        scheduler.critical_successor("C"),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
+
+#[test]
+fn test_free_slack() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 3);
+    scheduler.add_task("B", vec!["A"], 5);
+    scheduler.add_task("C", vec!["A"], 1);
+    scheduler.add_task("E", vec!["C"], 1);
+    scheduler.add_task("D", vec!["B", "E"], 1);
+
+    let total = scheduler.slack_analysis().unwrap();
+    let free = scheduler.free_slack().unwrap();
+    // C and E share 3 of slack before D, but C can't slip without moving E
+    assert_eq!((total["C"], free["C"]), (3, 0));
+    assert_eq!((total["E"], free["E"]), (3, 3));
+    for name in ["A", "B", "D"] {
+        assert_eq!((total[name], free[name]), (0, 0));
+    }
+}
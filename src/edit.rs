@@ -0,0 +1,556 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    AddError, Mutation, RecomputeReason, ScheduleError, ScheduledTask, Task, TaskScheduler,
+};
+
+/// The tasks a composite replaced, kept inside it by `collapse`
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Collapsed {
+    /// The collapsed tasks as they were, sorted by name
+    pub tasks: Vec<Task>,
+    /// Every outside task that waited on them, as it was before being rewired
+    pub dependents: Vec<Task>,
+}
+
+impl TaskScheduler {
+    /// Move all of `other`'s tasks into this scheduler.
+    ///
+    /// Tasks of `other` may depend on tasks that only exist in `self` (and the other way around),
+    /// those edges are wired up as if the tasks had been added to one scheduler.
+    /// If any name exists in both, nothing is merged and the (alphabetically first) duplicate is reported
+    pub fn merge(&mut self, other: TaskScheduler) -> Result<(), AddError> {
+        let mut duplicates: Vec<&String> = other
+            .tasks
+            .keys()
+            .filter(|name| self.tasks.contains_key(*name))
+            .collect();
+        duplicates.sort();
+        if let Some(duplicate) = duplicates.first() {
+            return Err(AddError::DuplicateTask(duplicate.to_string()));
+        }
+
+        // insert in a stable order, so reverse-dependency lists don't depend on hashmap order
+        let mut tasks: Vec<_> = other.tasks.into_values().collect();
+        tasks.sort_by(|a, b| a.name.cmp(&b.name));
+        for task in tasks {
+            self.insert_task(task);
+        }
+        Ok(())
+    }
+
+    /// Remove a single task, detaching it from both its dependencies and its dependents.
+    /// Dependents stay in the scheduler and simply no longer wait for it
+    pub(crate) fn remove_node(&mut self, name: &str) -> Option<Task> {
+        let task = self.tasks.remove(name)?;
+        self.first_level_dep.remove(name);
+
+        for dep in &task.dependencies {
+            if let Some(dependents) = self.dependents.get_mut(dep) {
+                dependents.retain(|d| d != name);
+                if dependents.is_empty() {
+                    self.dependents.remove(dep);
+                }
+            }
+        }
+        for dependent in self.dependents.remove(name).unwrap_or_default() {
+            if let Some(t) = self.tasks.get_mut(&dependent) {
+                t.dependencies.retain(|d| d != name);
+                t.lags.remove(name);
+                self.first_level_dep
+                    .insert(dependent.clone(), t.dependencies.len());
+            }
+        }
+        // an OR-group left without members has nothing to wait for, so it is dropped
+        for t in self.tasks.values_mut() {
+            t.on_success.retain(|branch| branch != name);
+            t.on_failure.retain(|branch| branch != name);
+            for group in &mut t.or_groups {
+                group.retain(|member| member != name);
+            }
+            t.or_groups.retain(|group| !group.is_empty());
+        }
+
+        self.record(|| Mutation::RemoveTask(name.to_string()));
+        self.notify(RecomputeReason::TaskRemoved);
+        Some(task)
+    }
+
+    /// Replace all dependencies of `name` with `new_deps` (duplicates dropped), keeping
+    /// `first_level_dep` and `dependents` in sync. Lags on dependencies that are gone are dropped.
+    /// Fails without changing anything if the task or any of the new dependencies is unknown
+    pub fn set_dependencies(
+        &mut self,
+        name: &str,
+        new_deps: Vec<&str>,
+    ) -> Result<(), ScheduleError> {
+        if !self.tasks.contains_key(name)
+            || new_deps.iter().any(|dep| !self.tasks.contains_key(*dep))
+        {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        let mut dependencies: Vec<String> = Vec::new();
+        for dep in new_deps {
+            if !dependencies.iter().any(|d| d == dep) {
+                dependencies.push(dep.to_string());
+            }
+        }
+
+        let task = self.tasks.get_mut(name).expect("checked above");
+        for dep in &task.dependencies {
+            if let Some(dependents) = self.dependents.get_mut(dep) {
+                dependents.retain(|d| d != name);
+                if dependents.is_empty() {
+                    self.dependents.remove(dep);
+                }
+            }
+        }
+        for dep in &dependencies {
+            self.dependents
+                .entry(dep.clone())
+                .or_default()
+                .push(name.to_string());
+        }
+        task.lags.retain(|dep, _| dependencies.contains(dep));
+        self.first_level_dep
+            .insert(name.to_string(), dependencies.len());
+        task.dependencies = dependencies;
+
+        self.record_task(name);
+        self.notify(RecomputeReason::DependenciesChanged);
+        Ok(())
+    }
+
+    /// Mark (or unmark) an existing task as protected against removal
+    pub fn set_protected(&mut self, name: &str, protected: bool) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.protected = protected;
+        self.record_task(name);
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Remove a single task and return it. Its dependents stay and no longer wait for it.
+    /// A protected task is refused with `Protected`
+    pub fn remove_task(&mut self, name: &str) -> Result<Task, ScheduleError> {
+        let task = self.tasks.get(name).ok_or(ScheduleError::NoTaskFound)?;
+        if task.protected {
+            return Err(ScheduleError::Protected(name.to_string()));
+        }
+        self.remove_task_forced(name)
+    }
+
+    /// Like `remove_task`, but removes the task even if it is protected
+    pub fn remove_task_forced(&mut self, name: &str) -> Result<Task, ScheduleError> {
+        self.remove_node(name).ok_or(ScheduleError::NoTaskFound)
+    }
+
+    /// Remove `name` together with everything that (transitively) depends on it.
+    /// Returns the removed names, sorted.
+    /// If any of them is protected nothing is removed, and the first protected one is reported
+    pub fn prune_task(&mut self, name: &str) -> Result<Vec<String>, ScheduleError> {
+        let mut removed: Vec<String> = self.reachable_from(&[name])?.into_iter().collect();
+        removed.sort();
+        if let Some(protected) = removed.iter().find(|task| self.tasks[*task].protected) {
+            return Err(ScheduleError::Protected(protected.clone()));
+        }
+        for task in &removed {
+            self.remove_node(task);
+        }
+        Ok(removed)
+    }
+
+    /// Replace `tasks` with one composite task `name` for hierarchical planning.
+    ///
+    /// The composite runs for the makespan of the sub-schedule (the collapsed tasks on their own,
+    /// as early as possible), waits on every dependency the region has outside of itself and
+    /// takes the place of its tasks in the dependencies of everything outside that waited on them.
+    /// Where several edges merge into one the largest lag is kept.
+    ///
+    /// The region must be contiguous: no path may leave it and come back in,
+    /// that would make the composite depend on itself and is a `ConstraintViolation`.
+    /// `name` may be one of `tasks`, otherwise it must be free (`DuplicateTask`).
+    /// Protected tasks are refused. Nothing changes on error. `expand` undoes it
+    pub fn collapse(&mut self, name: &str, tasks: &[&str]) -> Result<(), ScheduleError> {
+        let members: HashSet<&str> = tasks.iter().copied().collect();
+        if members.is_empty() {
+            return Err(ScheduleError::ConstraintViolation(name.to_string()));
+        }
+        if members
+            .iter()
+            .any(|member| !self.tasks.contains_key(*member))
+        {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        if self.tasks.contains_key(name) && !members.contains(name) {
+            return Err(ScheduleError::DuplicateTask(name.to_string()));
+        }
+        let mut sorted: Vec<&str> = members.iter().copied().collect();
+        sorted.sort();
+        if let Some(protected) = sorted.iter().find(|member| self.tasks[**member].protected) {
+            return Err(ScheduleError::Protected(protected.to_string()));
+        }
+
+        // everything outside that waits on the region directly
+        let mut outside: Vec<&str> = sorted
+            .iter()
+            .flat_map(|member| self.dependents.get(*member).into_iter().flatten())
+            .map(String::as_str)
+            .filter(|dependent| !members.contains(dependent))
+            .collect();
+        outside.sort();
+        outside.dedup();
+        if self
+            .reachable_from(&outside)?
+            .iter()
+            .any(|task| members.contains(task.as_str()))
+        {
+            return Err(ScheduleError::ConstraintViolation(name.to_string()));
+        }
+
+        let mut region = self.detached_copy();
+        for task in self
+            .tasks
+            .keys()
+            .filter(|task| !members.contains(task.as_str()))
+        {
+            region.remove_node(task);
+        }
+        let duration = region
+            .schedule_asap()?
+            .iter()
+            .map(ScheduledTask::finish)
+            .max()
+            .unwrap_or(0);
+
+        let mut composite = Task {
+            name: name.to_string(),
+            duration,
+            collapsed: Some(Collapsed {
+                tasks: sorted
+                    .iter()
+                    .map(|member| self.tasks[*member].clone())
+                    .collect(),
+                dependents: outside
+                    .iter()
+                    .map(|dependent| self.tasks[*dependent].clone())
+                    .collect(),
+            }),
+            ..Default::default()
+        };
+        for member in &sorted {
+            let task = &self.tasks[*member];
+            for dep in task
+                .dependencies
+                .iter()
+                .filter(|dep| !members.contains(dep.as_str()))
+            {
+                if !composite.dependencies.contains(dep) {
+                    composite.dependencies.push(dep.clone());
+                }
+                if task.lag(dep) > 0 {
+                    let lag = composite.lags.entry(dep.clone()).or_insert(0);
+                    *lag = (*lag).max(task.lag(dep));
+                }
+            }
+        }
+
+        // the region's edges in each dependent's list merge into one, where the first one was
+        let rewired: Vec<Task> = outside
+            .iter()
+            .map(|dependent| {
+                let mut task = self.tasks[*dependent].clone();
+                let lag = sorted
+                    .iter()
+                    .map(|member| task.lag(member))
+                    .max()
+                    .unwrap_or(0);
+                let mut dependencies: Vec<String> = Vec::new();
+                for dep in &task.dependencies {
+                    if !members.contains(dep.as_str()) {
+                        dependencies.push(dep.clone());
+                    } else if !dependencies.iter().any(|d| d == name) {
+                        dependencies.push(name.to_string());
+                    }
+                }
+                task.dependencies = dependencies;
+                task.lags.retain(|dep, _| !members.contains(dep.as_str()));
+                if lag > 0 {
+                    task.lags.insert(name.to_string(), lag);
+                }
+                task
+            })
+            .collect();
+
+        for member in &sorted {
+            self.remove_node(member);
+        }
+        self.insert_task(composite);
+        for task in rewired {
+            let dependent = task.name.clone();
+            self.replace_task(task);
+            self.record_task(&dependent);
+            self.notify(RecomputeReason::DependenciesChanged);
+        }
+        Ok(())
+    }
+
+    /// Undo a `collapse`: put the collapsed tasks back in place of `composite`
+    /// and return their names, sorted.
+    ///
+    /// Dependents of the composite go back to their dependencies and lags from before the
+    /// collapse, anything else about them is left as it is now.
+    /// Edges to or from the composite that were added after the collapse are dropped.
+    /// A task that was not made by `collapse` is a `ConstraintViolation`,
+    /// and a restored name that has been taken since is a `DuplicateTask`
+    pub fn expand(&mut self, composite: &str) -> Result<Vec<String>, ScheduleError> {
+        let task = self
+            .tasks
+            .get(composite)
+            .ok_or(ScheduleError::NoTaskFound)?;
+        let Some(collapsed) = task.collapsed.clone() else {
+            return Err(ScheduleError::ConstraintViolation(composite.to_string()));
+        };
+        if let Some(taken) = collapsed
+            .tasks
+            .iter()
+            .find(|task| task.name != composite && self.tasks.contains_key(&task.name))
+        {
+            return Err(ScheduleError::DuplicateTask(taken.name.clone()));
+        }
+        let restored: Vec<String> = collapsed
+            .tasks
+            .iter()
+            .map(|task| task.name.clone())
+            .collect();
+        let before: HashMap<&str, &Task> = collapsed
+            .dependents
+            .iter()
+            .map(|task| (task.name.as_str(), task))
+            .collect();
+
+        let rewired: Vec<Task> = self
+            .dependents
+            .get(composite)
+            .into_iter()
+            .flatten()
+            .filter_map(|dependent| {
+                let mut task = self.tasks[dependent].clone();
+                let old = before.get(dependent.as_str())?;
+                let mut dependencies: Vec<String> = old
+                    .dependencies
+                    .iter()
+                    .filter(|dep| restored.contains(dep) || task.dependencies.contains(dep))
+                    .cloned()
+                    .collect();
+                for dep in &task.dependencies {
+                    if dep != composite && !dependencies.contains(dep) {
+                        dependencies.push(dep.clone());
+                    }
+                }
+                task.lags.remove(composite);
+                for dep in &restored {
+                    if let Some(&lag) = old.lags.get(dep) {
+                        task.lags.insert(dep.clone(), lag);
+                    }
+                }
+                task.dependencies = dependencies;
+                Some(task)
+            })
+            .collect();
+
+        self.remove_node(composite);
+        for task in collapsed.tasks {
+            self.insert_task(task);
+        }
+        for task in rewired {
+            let dependent = task.name.clone();
+            self.replace_task(task);
+            self.record_task(&dependent);
+            self.notify(RecomputeReason::DependenciesChanged);
+        }
+        Ok(restored)
+    }
+}
+
+#[test]
+fn test_merge() {
+    let mut first = TaskScheduler::new();
+    first.add_task("A", vec![], 3);
+    first.add_task("B", vec!["A"], 2);
+
+    // D also waits on B, which only `first` defines
+    let mut second = TaskScheduler::new();
+    second.add_task("C", vec![], 1);
+    second.add_task("D", vec!["C", "B"], 4);
+
+    first.merge(second).unwrap();
+    assert_eq!(first.tasks.len(), 4);
+    assert_eq!(first.dependents["B"], vec!["D".to_string()]);
+
+    let order = first.topological_order().unwrap();
+    assert_eq!(order.len(), 4);
+    let position = |name: &str| order.iter().position(|n| n == name).unwrap();
+    assert!(position("A") < position("B"));
+    assert!(position("B") < position("D"));
+    assert!(position("C") < position("D"));
+}
+
+#[test]
+fn test_merge_collision_is_atomic() {
+    let mut first = crate::example_scheduler();
+    let mut second = TaskScheduler::new();
+    second.add_task("E", vec![], 1);
+    second.add_task("B", vec![], 1);
+
+    assert_eq!(
+        first.merge(second),
+        Err(AddError::DuplicateTask("B".to_string()))
+    );
+    assert_eq!(first.tasks.len(), 4);
+    assert!(!first.tasks.contains_key("E"));
+}
+
+#[test]
+fn test_prune_task() {
+    let mut scheduler = crate::example_scheduler();
+    assert_eq!(
+        scheduler.prune_task("A"),
+        Ok(vec![
+            "A".to_string(),
+            "B".to_string(),
+            "C".to_string(),
+            "D".to_string()
+        ])
+    );
+    assert!(scheduler.tasks.is_empty());
+    assert!(scheduler.dependents.is_empty());
+    assert!(scheduler.first_level_dep.is_empty());
+}
+
+#[test]
+fn test_prune_task_repairs_survivors() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.add_task("E", vec!["A"], 1);
+
+    // D goes with B, C and E survive
+    assert_eq!(
+        scheduler.prune_task("B"),
+        Ok(vec!["B".to_string(), "D".to_string()])
+    );
+    assert_eq!(
+        scheduler.dependents["A"],
+        vec!["C".to_string(), "E".to_string()]
+    );
+    assert!(!scheduler.dependents.contains_key("C"));
+    assert_eq!(scheduler.schedule_tasks().unwrap().len(), 3);
+    assert_eq!(scheduler.prune_task("X"), Err(ScheduleError::NoTaskFound));
+}
+
+#[test]
+fn test_set_dependencies() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.set_dependencies("D", vec!["A", "A"]).unwrap();
+
+    assert_eq!(scheduler.tasks["D"].dependencies, vec!["A"]);
+    assert_eq!(scheduler.first_level_dep["D"], 1);
+    assert!(!scheduler.dependents.contains_key("B"));
+    assert!(scheduler.dependents["A"].contains(&"D".to_string()));
+
+    // D no longer waits for B, so it can start as soon as A is done
+    let start_of_d = scheduler
+        .schedule_asap()
+        .unwrap()
+        .into_iter()
+        .find(|t| t.name == "D")
+        .unwrap()
+        .start;
+    assert_eq!(start_of_d, 3);
+
+    assert_eq!(
+        scheduler.set_dependencies("D", vec!["missing"]),
+        Err(ScheduleError::NoTaskFound)
+    );
+    assert_eq!(scheduler.tasks["D"].dependencies, vec!["A"]);
+}
+
+#[test]
+fn test_protected_tasks() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.set_protected("D", true).unwrap();
+
+    assert_eq!(
+        scheduler.remove_task("D"),
+        Err(ScheduleError::Protected("D".into()))
+    );
+    // B itself is fine, but pruning it would take D along
+    assert_eq!(
+        scheduler.prune_task("B"),
+        Err(ScheduleError::Protected("D".into()))
+    );
+    assert_eq!(scheduler.tasks.len(), 4);
+
+    assert_eq!(scheduler.remove_task("C").unwrap().name, "C");
+    assert_eq!(scheduler.remove_task_forced("D").unwrap().name, "D");
+    assert_eq!(scheduler.remove_task("D"), Err(ScheduleError::NoTaskFound));
+    assert_eq!(scheduler.tasks.len(), 2);
+}
+
+#[test]
+fn test_collapse() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.collapse("BC", &["B", "C"]).unwrap();
+
+    assert_eq!(scheduler.tasks.len(), 3);
+    // B and C run side by side, so the composite takes as long as B
+    assert_eq!(scheduler.tasks["BC"].duration, 2);
+    assert_eq!(scheduler.tasks["BC"].dependencies, vec!["A"]);
+    assert_eq!(scheduler.tasks["D"].dependencies, vec!["BC"]);
+    assert_eq!(scheduler.dependents["A"], vec!["BC".to_string()]);
+    assert_eq!(
+        scheduler.schedule_tasks().unwrap(),
+        vec![
+            ("A".to_string(), 0, 3),
+            ("BC".to_string(), 3, 2),
+            ("D".to_string(), 5, 4),
+        ]
+    );
+}
+
+#[test]
+fn test_collapse_rejects_non_contiguous_region() {
+    let mut scheduler = crate::example_scheduler();
+    // A -> B -> D leaves the region through B and comes back in
+    assert_eq!(
+        scheduler.collapse("AD", &["A", "D"]),
+        Err(ScheduleError::ConstraintViolation("AD".to_string()))
+    );
+    assert_eq!(
+        scheduler.collapse("C", &["A", "B"]),
+        Err(ScheduleError::DuplicateTask("C".to_string()))
+    );
+    assert_eq!(scheduler, crate::example_scheduler());
+}
+
+#[test]
+fn test_expand_undoes_collapse() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.set_duration("C", 5).unwrap();
+    let original = scheduler.detached_copy();
+
+    scheduler.collapse("BC", &["B", "C"]).unwrap();
+    assert_eq!(
+        TaskScheduler::from_bytes(&scheduler.to_bytes()).unwrap(),
+        scheduler
+    );
+    assert_eq!(
+        scheduler.expand("BC"),
+        Ok(vec!["B".to_string(), "C".to_string()])
+    );
+    assert_eq!(scheduler, original);
+
+    assert_eq!(
+        scheduler.expand("A"),
+        Err(ScheduleError::ConstraintViolation("A".to_string()))
+    );
+}
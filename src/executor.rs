@@ -0,0 +1,367 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{alternatives::OrGroups, ScheduleError, Task, TaskScheduler};
+
+/// What happened to each task during `run_parallel`. Every list is sorted by name
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct RunReport {
+    /// Tasks whose job returned `Ok`
+    pub succeeded: Vec<String>,
+    /// Tasks whose job returned an error (or panicked), with the error message
+    pub failed: Vec<(String, String)>,
+    /// Tasks whose job didn't finish within the timeout
+    pub timed_out: Vec<String>,
+    /// Tasks never started because something they depend on failed or timed out
+    pub skipped: Vec<String>,
+}
+
+impl TaskScheduler {
+    /// Actually execute `job` for every task, running up to `workers` jobs at once
+    /// and starting each task only after all of its dependencies succeeded,
+    /// along with at least one task out of each of its OR-groups.
+    ///
+    /// When a job fails, panics, or takes longer than `timeout`, everything
+    /// that (transitively) depends on it is skipped, while unrelated tasks keep running.
+    /// Rust can't forcibly stop a thread, so a timed out job keeps running in the
+    /// background; the scheduler just stops waiting for it and ignores its result.
+    /// That is also why `job` has to be `'static`
+    pub fn run_parallel<F>(
+        &self,
+        workers: usize,
+        timeout: Option<Duration>,
+        job: F,
+    ) -> Result<RunReport, ScheduleError>
+    where
+        F: Fn(&Task) -> Result<(), String> + Send + Sync + 'static,
+    {
+        // refuse to start on a graph that could never finish
+        self.schedule_tasks()?;
+
+        let job = Arc::new(job);
+        let (sender, receiver) = mpsc::channel::<(String, Result<(), String>)>();
+        let mut report = RunReport::default();
+        let mut in_degree = self.ready_degrees();
+        let mut or_groups = OrGroups::new(&self.tasks);
+        // a branch task also waits for the task it is a branch of
+        for task in self.tasks.values() {
+            for branch in task.on_success.iter().chain(&task.on_failure) {
+                if let Some(degree) = in_degree.get_mut(branch) {
+                    *degree += 1;
+                }
+            }
+        }
+        let mut roots: Vec<String> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(name, _)| name.clone())
+            .collect();
+        roots.sort();
+        let mut ready: VecDeque<String> = roots.into();
+        // running tasks and the moment we give up on them
+        let mut in_flight: HashMap<String, Option<Instant>> = HashMap::new();
+        let mut skipped: HashSet<String> = HashSet::new();
+
+        loop {
+            while in_flight.len() < workers.max(1) {
+                let Some(name) = ready.pop_front() else {
+                    break;
+                };
+                let task = self.tasks[&name].clone();
+                let job = Arc::clone(&job);
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    let result = catch_unwind(AssertUnwindSafe(|| job(&task)))
+                        .unwrap_or_else(|_| Err("job panicked".to_string()));
+                    // the receiver is gone if the run already ended, nothing to report then
+                    let _ = sender.send((task.name, result));
+                });
+                in_flight.insert(name, timeout.map(|limit| Instant::now() + limit));
+            }
+
+            if in_flight.is_empty() {
+                break;
+            }
+
+            // wait for the next result, but no longer than the closest timeout
+            let closest = in_flight.values().flatten().min().copied();
+            let received = match closest {
+                Some(at) => receiver.recv_timeout(at.saturating_duration_since(Instant::now())),
+                None => receiver
+                    .recv()
+                    .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+            };
+
+            // tasks that can't run anymore, along with everything after them
+            let mut cut_off: Vec<&String> = Vec::new();
+            match received {
+                Ok((name, result)) => {
+                    // a result of an already timed out job is ignored
+                    if in_flight.remove(&name).is_none() {
+                        continue;
+                    }
+                    let task = &self.tasks[&name];
+                    let dependents = self.dependents.get(&name).into_iter().flatten();
+                    // the branch matching the outcome goes on, the other one is cut off
+                    let (go_on, not_taken) = match &result {
+                        Ok(()) => (&task.on_success, &task.on_failure),
+                        Err(_) => (&task.on_failure, &task.on_success),
+                    };
+                    cut_off.extend(not_taken);
+                    let released: Vec<&str> = match &result {
+                        // only a task that succeeded counts towards an OR-group
+                        Ok(()) => dependents
+                            .chain(go_on)
+                            .map(String::as_str)
+                            .chain(or_groups.finished(&name))
+                            .collect(),
+                        Err(_) => {
+                            // whatever depends on a failed task can't run anymore
+                            cut_off.extend(dependents);
+                            go_on.iter().map(String::as_str).collect()
+                        }
+                    };
+                    for neighbor in released {
+                        if let Some(degree) = in_degree.get_mut(neighbor) {
+                            *degree -= 1;
+                            if *degree == 0 && !skipped.contains(neighbor) {
+                                ready.push_back(neighbor.to_string());
+                            }
+                        }
+                    }
+                    match result {
+                        Ok(()) => report.succeeded.push(name),
+                        Err(message) => report.failed.push((name, message)),
+                    }
+                }
+                Err(_) => {
+                    let now = Instant::now();
+                    let expired: Vec<String> = in_flight
+                        .iter()
+                        .filter(|(_, at)| at.is_some_and(|at| at <= now))
+                        .map(|(name, _)| name.clone())
+                        .collect();
+                    for name in expired {
+                        in_flight.remove(&name);
+                        // nothing after a timed out task runs, whichever branch
+                        let task = &self.tasks[&name];
+                        cut_off.extend(self.dependents.get(&name).into_iter().flatten());
+                        cut_off.extend(task.on_success.iter().chain(&task.on_failure));
+                        report.timed_out.push(name);
+                    }
+                }
+            }
+
+            skipped.extend(self.run_downstream(cut_off));
+        }
+
+        // a task still waiting here had every member of one of its OR-groups fail
+        let stuck: Vec<&String> = self
+            .tasks
+            .keys()
+            .filter(|name| in_degree.get(*name).is_some_and(|&degree| degree > 0))
+            .collect();
+        skipped.extend(self.run_downstream(stuck));
+
+        report.skipped = skipped.into_iter().collect();
+        report.succeeded.sort();
+        report.failed.sort();
+        report.timed_out.sort();
+        report.skipped.sort();
+        Ok(report)
+    }
+}
+
+impl TaskScheduler {
+    /// `starts` and every task after them for `run_parallel`: their dependents and branches,
+    /// transitively
+    fn run_downstream<'a>(&'a self, starts: Vec<&'a String>) -> HashSet<String> {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut stack = starts;
+        while let Some(name) = stack.pop() {
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            stack.extend(self.dependents.get(name).into_iter().flatten());
+            if let Some(task) = self.tasks.get(name) {
+                stack.extend(task.on_success.iter().chain(&task.on_failure));
+            }
+        }
+        seen
+    }
+}
+
+#[test]
+fn test_run_parallel() {
+    use std::sync::Mutex;
+
+    let scheduler = crate::example_scheduler();
+    let finished = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&finished);
+
+    let report = scheduler
+        .run_parallel(2, None, move |task| {
+            log.lock().unwrap().push(task.name.clone());
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(report.succeeded, vec!["A", "B", "C", "D"]);
+
+    // dependencies always ran first
+    let finished = finished.lock().unwrap();
+    assert_eq!(finished.first().map(String::as_str), Some("A"));
+    assert_eq!(finished.last().map(String::as_str), Some("D"));
+}
+
+#[test]
+fn test_run_parallel_failure_skips_dependents() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.add_task("E", vec!["A"], 1);
+
+    let report = scheduler
+        .run_parallel(2, None, |task| match task.name.as_str() {
+            "B" => Err("boom".to_string()),
+            _ => Ok(()),
+        })
+        .unwrap();
+    assert_eq!(report.succeeded, vec!["A", "C", "E"]);
+    assert_eq!(report.failed, vec![("B".to_string(), "boom".to_string())]);
+    assert_eq!(report.skipped, vec!["D"]);
+}
+
+#[test]
+fn test_run_parallel_timeout() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("Slow", vec![], 1);
+    scheduler.add_task("AfterSlow", vec!["Slow"], 1);
+    scheduler.add_task("Fast", vec![], 1);
+
+    let started = Instant::now();
+    let report = scheduler
+        .run_parallel(2, Some(Duration::from_millis(50)), |task| {
+            if task.name == "Slow" {
+                thread::sleep(Duration::from_secs(2));
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    assert_eq!(report.succeeded, vec!["Fast"]);
+    assert_eq!(report.timed_out, vec!["Slow"]);
+    assert_eq!(report.skipped, vec!["AfterSlow"]);
+    // we did not wait for the slow job to actually finish
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn test_run_parallel_branches() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("deploy", vec![], 1);
+    scheduler.add_task("announce", vec![], 1);
+    scheduler.add_task("rollback", vec![], 1);
+    scheduler.add_task("page", vec!["rollback"], 1);
+    scheduler
+        .set_branches("deploy", vec!["announce"], vec!["rollback"])
+        .unwrap();
+
+    let report = scheduler
+        .run_parallel(2, None, |task| match task.name.as_str() {
+            "deploy" => Err("boom".to_string()),
+            _ => Ok(()),
+        })
+        .unwrap();
+    assert_eq!(
+        report.failed,
+        vec![("deploy".to_string(), "boom".to_string())]
+    );
+    assert_eq!(report.succeeded, vec!["page", "rollback"]);
+    assert_eq!(report.skipped, vec!["announce"]);
+
+    let report = scheduler.run_parallel(2, None, |_| Ok(())).unwrap();
+    assert_eq!(report.succeeded, vec!["announce", "deploy"]);
+    assert_eq!(report.skipped, vec!["page", "rollback"]);
+
+    assert_eq!(
+        scheduler.set_branches("deploy", vec!["missing"], vec![]),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
+
+#[test]
+fn test_run_parallel_removed_branch() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 1);
+    scheduler.add_task("B", vec![], 1);
+    scheduler.set_branches("A", vec!["B"], vec![]).unwrap();
+    scheduler.remove_task("B").unwrap();
+    assert!(scheduler.tasks["A"].on_success.is_empty());
+
+    let report = scheduler.run_parallel(2, None, |_| Ok(())).unwrap();
+    assert_eq!(report.succeeded, vec!["A"]);
+}
+
+#[test]
+fn test_set_branches_rejects_loops() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 1);
+    scheduler.add_task("B", vec![], 1);
+    scheduler.add_task("C", vec!["A"], 1);
+
+    assert_eq!(
+        scheduler.set_branches("A", vec!["A"], vec![]),
+        Err(ScheduleError::CycleDetected)
+    );
+    scheduler.set_branches("A", vec!["B"], vec![]).unwrap();
+    assert_eq!(
+        scheduler.set_branches("B", vec![], vec!["A"]),
+        Err(ScheduleError::CycleDetected)
+    );
+    // C already waits on A, so it can't branch back to it
+    assert_eq!(
+        scheduler.set_branches("C", vec![], vec!["A"]),
+        Err(ScheduleError::CycleDetected)
+    );
+    scheduler.set_branches("C", vec!["B"], vec![]).unwrap();
+    // a rejected call changes nothing
+    assert!(scheduler.tasks["B"].on_success.is_empty());
+    assert_eq!(scheduler.tasks["A"].on_success, ["B"]);
+}
+
+#[test]
+fn test_run_parallel_or_groups() {
+    use std::sync::Mutex;
+
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("slow", vec![], 1);
+    scheduler
+        .add_task_with_or_deps("X", vec![], vec![vec!["slow"]], 1)
+        .unwrap();
+    scheduler.add_task("after", vec!["X"], 1);
+
+    let finished = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&finished);
+    let report = scheduler
+        .run_parallel(2, None, move |task| {
+            if task.name == "slow" {
+                thread::sleep(Duration::from_millis(20));
+            }
+            log.lock().unwrap().push(task.name.clone());
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(report.succeeded, vec!["X", "after", "slow"]);
+    assert_eq!(*finished.lock().unwrap(), vec!["slow", "X", "after"]);
+
+    // a failed member does not satisfy the group
+    let report = scheduler
+        .run_parallel(2, None, |task| match task.name.as_str() {
+            "slow" => Err("boom".to_string()),
+            _ => Ok(()),
+        })
+        .unwrap();
+    assert!(report.succeeded.is_empty());
+    assert_eq!(report.skipped, vec!["X", "after"]);
+}
@@ -0,0 +1,662 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+
+use crate::{ScheduleError, TaskScheduler};
+
+/// Text for inside a quoted DOT string, escaped while being written
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            if c == '"' || c == '\\' {
+                f.write_str("\\")?;
+            }
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+/// `value` as a JSON string literal, quotes included
+pub(crate) fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Markdown table of `(name, start, duration)` entries, with their finish times
+pub(crate) fn markdown_table<'a>(
+    entries: impl IntoIterator<Item = (Cow<'a, str>, u32, u32)>,
+) -> String {
+    let mut out = String::from("| Name | Start | Duration | Finish |\n|---|---|---|---|\n");
+    for (name, start, duration) in entries {
+        out.push_str(&format!(
+            "| {} | {start} | {duration} | {} |\n",
+            name.replace('|', "\\|"),
+            start + duration
+        ));
+    }
+    out
+}
+
+/// `value` escaped for inside a quoted Prometheus label value
+fn prometheus_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Optional output-only renaming of tasks, see `with_name_mapper`
+type Rename<'r> = Option<&'r dyn Fn(&str) -> String>;
+
+/// `name` as it should appear in the output
+fn shown<'a>(name: &'a str, rename: Rename) -> Cow<'a, str> {
+    match rename {
+        Some(rename) => Cow::Owned(rename(name)),
+        None => Cow::Borrowed(name),
+    }
+}
+
+/// Exports of a scheduler with every task name passed through a mapper first,
+/// see `TaskScheduler::with_name_mapper`
+pub struct NameMapped<'s, F> {
+    scheduler: &'s TaskScheduler,
+    mapper: F,
+}
+
+impl<F: Fn(&str) -> String> NameMapped<'_, F> {
+    /// `TaskScheduler::to_dot` with mapped names
+    pub fn to_dot(&self) -> String {
+        let mut bytes = Vec::new();
+        self.write_dot(&mut bytes)
+            .expect("writing to a Vec does not fail");
+        String::from_utf8(bytes).expect("DOT output is built from strings")
+    }
+
+    /// `TaskScheduler::write_dot` with mapped names
+    pub fn write_dot<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        self.scheduler.write_dot_renamed(w, Some(&self.mapper))
+    }
+
+    /// `TaskScheduler::to_ndjson` with mapped names
+    pub fn to_ndjson(&self) -> Result<String, ScheduleError> {
+        self.scheduler.ndjson_renamed(Some(&self.mapper))
+    }
+
+    /// `TaskScheduler::to_markdown_table` with mapped names
+    pub fn to_markdown_table(&self) -> Result<String, ScheduleError> {
+        self.scheduler.markdown_table_renamed(Some(&self.mapper))
+    }
+}
+
+impl TaskScheduler {
+    /// Export with task names transformed by `mapper`, e.g. to strip characters the target
+    /// format can't take. Only the output is affected, the scheduler keeps its names:
+    /// `scheduler.with_name_mapper(|name| name.replace(':', "_")).to_dot()`
+    pub fn with_name_mapper<F: Fn(&str) -> String>(&self, mapper: F) -> NameMapped<'_, F> {
+        NameMapped {
+            scheduler: self,
+            mapper,
+        }
+    }
+
+    /// Graph as an adjacency matrix, along with the names indexing it.
+    ///
+    /// Names are sorted; `matrix[i][j]` is 1 iff task `i` depends on task `j`.
+    /// Dependencies on tasks that were never added are not represented
+    pub fn adjacency_matrix(&self) -> (Vec<String>, Vec<Vec<u8>>) {
+        let mut names: Vec<String> = self.tasks.keys().cloned().collect();
+        names.sort();
+        let index: HashMap<&String, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name, i))
+            .collect();
+
+        let mut matrix = vec![vec![0u8; names.len()]; names.len()];
+        for (i, name) in names.iter().enumerate() {
+            for dep in &self.tasks[name].dependencies {
+                if let Some(&j) = index.get(dep) {
+                    matrix[i][j] = 1;
+                }
+            }
+        }
+        (names, matrix)
+    }
+
+    /// Single-threaded schedule as newline-delimited JSON, one object per task in execution order:
+    /// `{"name":"A","start":0,"duration":3,"finish":3}`. Every line ends with a newline
+    pub fn to_ndjson(&self) -> Result<String, ScheduleError> {
+        self.ndjson_renamed(None)
+    }
+
+    fn ndjson_renamed(&self, rename: Rename) -> Result<String, ScheduleError> {
+        let mut out = String::new();
+        for (name, start, duration) in self.schedule_tasks()? {
+            out.push_str(&format!(
+                "{{\"name\":{},\"start\":{start},\"duration\":{duration},\"finish\":{}}}\n",
+                json_string(&shown(&name, rename)),
+                start + duration
+            ));
+        }
+        Ok(out)
+    }
+
+    /// Topological levels as JSON for a frontend that lays the graph out in layers:
+    /// `[{"level":0,"tasks":[{"name":"A","duration":3}]},...]`, see `schedule_levels`
+    pub fn to_levels_json(&self) -> Result<String, ScheduleError> {
+        let levels: Vec<String> = self
+            .schedule_levels()?
+            .iter()
+            .enumerate()
+            .map(|(level, names)| {
+                let tasks: Vec<String> = names
+                    .iter()
+                    .map(|name| {
+                        format!(
+                            "{{\"name\":{},\"duration\":{}}}",
+                            json_string(name),
+                            self.tasks[name].duration
+                        )
+                    })
+                    .collect();
+                format!("{{\"level\":{level},\"tasks\":[{}]}}", tasks.join(","))
+            })
+            .collect();
+        Ok(format!("[{}]", levels.join(",")))
+    }
+
+    /// The graph in JSON Graph Format: every task a node keyed by its name, with its duration
+    /// as metadata, and an edge from every dependency to its dependent.
+    /// Sorted, so the output is stable. Dependencies that were never added have no node,
+    /// so their edges are left out
+    pub fn to_jgf(&self) -> String {
+        let mut names: Vec<&String> = self.tasks.keys().collect();
+        names.sort();
+
+        let nodes: Vec<String> = names
+            .iter()
+            .map(|name| {
+                format!(
+                    "{}:{{\"label\":{},\"metadata\":{{\"duration\":{}}}}}",
+                    json_string(name),
+                    json_string(name),
+                    self.tasks[*name].duration
+                )
+            })
+            .collect();
+        let mut edges: Vec<(&String, &String)> = names
+            .iter()
+            .flat_map(|name| {
+                self.tasks[*name]
+                    .dependencies
+                    .iter()
+                    .filter(|dep| self.tasks.contains_key(*dep))
+                    .map(move |dep| (dep, *name))
+            })
+            .collect();
+        edges.sort();
+        let edges: Vec<String> = edges
+            .into_iter()
+            .map(|(source, target)| {
+                format!(
+                    "{{\"source\":{},\"target\":{}}}",
+                    json_string(source),
+                    json_string(target)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"graph\":{{\"directed\":true,\"nodes\":{{{}}},\"edges\":[{}]}}}}",
+            nodes.join(","),
+            edges.join(",")
+        )
+    }
+
+    /// Critical path in the folded stack format flamegraph tools read.
+    /// Every task on the path gets a line with the path up to it and its own duration:
+    ///
+    /// ```text
+    /// A 3
+    /// A;B 2
+    /// A;B;D 4
+    /// ```
+    pub fn to_folded(&self) -> Result<String, ScheduleError> {
+        let mut out = String::new();
+        let mut stack: Vec<&str> = Vec::new();
+        let path = self.critical_path()?;
+        for name in &path {
+            stack.push(name);
+            out.push_str(&format!(
+                "{} {}\n",
+                stack.join(";"),
+                self.tasks[name].duration
+            ));
+        }
+        Ok(out)
+    }
+
+    /// `schedule_asap` as a PlantUML Gantt chart, time units as days:
+    ///
+    /// ```text
+    /// @startgantt
+    /// [A] lasts 3 days
+    /// [B] lasts 2 days
+    /// [B] starts at [A]'s end
+    /// ...
+    /// @endgantt
+    /// ```
+    ///
+    /// PlantUML takes one start constraint per task, so a task with several dependencies
+    /// starts after the one that finishes last (plus its lag), ties going to the smaller name.
+    /// That gives the same timeline, the other edges are not drawn
+    pub fn to_plantuml_gantt(&self) -> Result<String, ScheduleError> {
+        let schedule = self.schedule_asap()?;
+        let finish: HashMap<&str, u32> = schedule
+            .iter()
+            .map(|task| (task.name.as_str(), task.finish()))
+            .collect();
+
+        let mut out = String::from("@startgantt\n");
+        for entry in &schedule {
+            let task = &self.tasks[&entry.name];
+            out.push_str(&format!("[{}] lasts {} days\n", entry.name, entry.duration));
+            let mut deps: Vec<&String> = task.dependencies.iter().collect();
+            deps.sort();
+            let binding = deps
+                .into_iter()
+                .rev()
+                .filter_map(|dep| Some((finish.get(dep.as_str())? + task.lag(dep), dep)))
+                .max_by_key(|&(ready, _)| ready);
+            match binding {
+                Some((_, dep)) if task.lag(dep) > 0 => out.push_str(&format!(
+                    "[{}] starts {} days after [{dep}]'s end\n",
+                    entry.name,
+                    task.lag(dep)
+                )),
+                Some((_, dep)) => {
+                    out.push_str(&format!("[{}] starts at [{dep}]'s end\n", entry.name))
+                }
+                None => {}
+            }
+        }
+        out.push_str("@endgantt\n");
+        Ok(out)
+    }
+
+    /// Single-threaded schedule in the Prometheus text exposition format: the
+    /// `task_scheduled_start` and `task_duration` gauges with one sample per task in execution
+    /// order, labelled by task name, then the `schedule_makespan` gauge
+    pub fn to_prometheus(&self) -> Result<String, ScheduleError> {
+        let schedule = self.schedule_tasks()?;
+        let makespan = schedule
+            .iter()
+            .map(|(_, start, duration)| start + duration)
+            .max()
+            .unwrap_or(0);
+
+        let mut out = String::new();
+        let mut family = |metric: &str, help: &str, value: fn(&(String, u32, u32)) -> u32| {
+            out.push_str(&format!("# HELP {metric} {help}\n# TYPE {metric} gauge\n"));
+            for entry in &schedule {
+                out.push_str(&format!(
+                    "{metric}{{task=\"{}\"}} {}\n",
+                    prometheus_label(&entry.0),
+                    value(entry)
+                ));
+            }
+        };
+        family(
+            "task_scheduled_start",
+            "Planned start time of the task",
+            |entry| entry.1,
+        );
+        family("task_duration", "Duration of the task", |entry| entry.2);
+        out.push_str(&format!(
+            "# HELP schedule_makespan Planned finish time of the last task\n\
+             # TYPE schedule_makespan gauge\n\
+             schedule_makespan {makespan}\n"
+        ));
+        Ok(out)
+    }
+
+    /// Single-threaded schedule as a GitHub-flavored Markdown table, one row per task
+    /// in execution order. Pipes in task names are escaped
+    pub fn to_markdown_table(&self) -> Result<String, ScheduleError> {
+        self.markdown_table_renamed(None)
+    }
+
+    fn markdown_table_renamed(&self, rename: Rename) -> Result<String, ScheduleError> {
+        let schedule = self.schedule_tasks()?;
+        Ok(markdown_table(schedule.iter().map(
+            |(name, start, duration)| (shown(name, rename), *start, *duration),
+        )))
+    }
+
+    /// Graphviz DOT rendering of the graph, see `write_dot`
+    pub fn to_dot(&self) -> String {
+        let mut bytes = Vec::new();
+        self.write_dot(&mut bytes)
+            .expect("writing to a Vec does not fail");
+        String::from_utf8(bytes).expect("DOT output is built from strings")
+    }
+
+    /// Stream the graph in Graphviz DOT format to `w`, one node or edge per line,
+    /// without building the whole document in memory.
+    ///
+    /// Nodes are labelled with their duration, edges point from a dependency to its dependent.
+    /// Both are written in name order, so the output is deterministic
+    pub fn write_dot<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        self.write_dot_renamed(w, None)
+    }
+
+    fn write_dot_renamed<W: io::Write>(&self, w: &mut W, rename: Rename) -> io::Result<()> {
+        let mut names: Vec<&String> = self.tasks.keys().collect();
+        names.sort();
+
+        writeln!(w, "digraph tasks {{")?;
+        for name in &names {
+            let duration = self.tasks[*name].duration;
+            let name = shown(name, rename);
+            let name = Escaped(&name);
+            writeln!(w, "    \"{name}\" [label=\"{name} ({duration})\"];")?;
+        }
+        for name in &names {
+            let mut deps: Vec<&String> = self.tasks[*name].dependencies.iter().collect();
+            deps.sort();
+            for dep in deps {
+                let (dep, name) = (shown(dep, rename), shown(name, rename));
+                writeln!(w, "    \"{}\" -> \"{}\";", Escaped(&dep), Escaped(&name))?;
+            }
+        }
+        writeln!(w, "}}")
+    }
+
+    /// Indented ASCII tree of everything `root` depends on, for reading in a terminal:
+    ///
+    /// ```text
+    /// D
+    /// ├─ B
+    /// │  └─ A
+    /// └─ C
+    ///    └─ A
+    /// ```
+    ///
+    /// Shared dependencies are repeated under every task needing them.
+    /// A dependency that leads back into its own branch is marked `(cycle)` and not expanded
+    pub fn print_tree(&self, root: &str) -> Result<String, ScheduleError> {
+        let task = self.tasks.get(root).ok_or(ScheduleError::NoTaskFound)?;
+        let mut lines = vec![root.to_string()];
+
+        // (name, prefix inherited from the parents, is last child, depth)
+        let push_children = |stack: &mut Vec<(String, String, bool, usize)>,
+                             deps: &[String],
+                             prefix: String,
+                             depth: usize| {
+            for (i, dep) in deps.iter().enumerate().rev() {
+                stack.push((dep.clone(), prefix.clone(), i + 1 == deps.len(), depth));
+            }
+        };
+        let mut stack = Vec::new();
+        push_children(&mut stack, &task.dependencies, String::new(), 1);
+
+        // names on the branch from the root down to the current node
+        let mut path = vec![root.to_string()];
+        while let Some((name, prefix, last, depth)) = stack.pop() {
+            path.truncate(depth);
+            let branch = if last { "└─ " } else { "├─ " };
+            if path.contains(&name) {
+                lines.push(format!("{prefix}{branch}{name} (cycle)"));
+                continue;
+            }
+            lines.push(format!("{prefix}{branch}{name}"));
+            path.push(name.clone());
+
+            if let Some(task) = self.tasks.get(&name) {
+                let child_prefix = format!("{prefix}{}", if last { "   " } else { "│  " });
+                push_children(&mut stack, &task.dependencies, child_prefix, depth + 1);
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+#[test]
+fn test_adjacency_matrix() {
+    let (names, matrix) = crate::example_scheduler().adjacency_matrix();
+    assert_eq!(names, vec!["A", "B", "C", "D"]);
+    assert_eq!(
+        matrix,
+        vec![
+            vec![0, 0, 0, 0],
+            vec![1, 0, 0, 0],
+            vec![1, 0, 0, 0],
+            vec![0, 1, 1, 0],
+        ]
+    );
+}
+
+#[test]
+fn test_print_tree() {
+    let scheduler = crate::example_scheduler();
+    assert_eq!(
+        scheduler.print_tree("D"),
+        Ok("D\n├─ B\n│  └─ A\n└─ C\n   └─ A".to_string())
+    );
+    assert_eq!(scheduler.print_tree("A"), Ok("A".to_string()));
+    assert_eq!(scheduler.print_tree("X"), Err(ScheduleError::NoTaskFound));
+}
+
+#[test]
+fn test_print_tree_marks_cycle() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec!["B"], 1);
+    scheduler.add_task("B", vec!["A"], 1);
+    assert_eq!(
+        scheduler.print_tree("A"),
+        Ok("A\n└─ B\n   └─ A (cycle)".to_string())
+    );
+}
+
+#[test]
+fn test_write_dot() {
+    let scheduler = crate::example_scheduler();
+    let mut bytes = Vec::new();
+    scheduler.write_dot(&mut bytes).unwrap();
+    let dot = String::from_utf8(bytes).unwrap();
+    assert_eq!(dot, scheduler.to_dot());
+
+    assert!(dot.starts_with("digraph tasks {\n    \"A\" [label=\"A (3)\"];\n"));
+    assert!(dot.contains("    \"B\" -> \"D\";\n    \"C\" -> \"D\";\n"));
+    assert!(dot.ends_with("}\n"));
+
+    let mut quoted = TaskScheduler::new();
+    quoted.add_task("say \"hi\"", vec![], 1);
+    assert!(quoted.to_dot().contains("\"say \\\"hi\\\"\""));
+}
+
+#[test]
+fn test_to_ndjson() {
+    let mut scheduler = crate::example_scheduler();
+    let ndjson = scheduler.to_ndjson().unwrap();
+    let lines: Vec<&str> = ndjson.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(
+        lines[0],
+        r#"{"name":"A","start":0,"duration":3,"finish":3}"#
+    );
+    assert_eq!(
+        lines[3],
+        r#"{"name":"D","start":6,"duration":4,"finish":10}"#
+    );
+
+    scheduler.add_task("say \"hi\"\n", vec!["D"], 1);
+    let last = scheduler
+        .to_ndjson()
+        .unwrap()
+        .lines()
+        .last()
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        last,
+        r#"{"name":"say \"hi\"\n","start":10,"duration":1,"finish":11}"#
+    );
+}
+
+#[test]
+fn test_to_folded() {
+    assert_eq!(
+        crate::example_scheduler().to_folded().unwrap(),
+        "A 3\nA;B 2\nA;B;D 4\n"
+    );
+}
+
+#[test]
+fn test_to_markdown_table() {
+    assert_eq!(
+        crate::example_scheduler().to_markdown_table().unwrap(),
+        "| Name | Start | Duration | Finish |\n\
+         |---|---|---|---|\n\
+         | A | 0 | 3 | 3 |\n\
+         | B | 3 | 2 | 5 |\n\
+         | C | 5 | 1 | 6 |\n\
+         | D | 6 | 4 | 10 |\n"
+    );
+}
+
+#[test]
+fn test_with_name_mapper() {
+    let scheduler = crate::example_scheduler();
+    let lower = scheduler.with_name_mapper(|name| name.to_lowercase());
+
+    let dot = lower.to_dot();
+    assert!(dot.contains("    \"a\" [label=\"a (3)\"];\n"));
+    assert!(dot.contains("    \"b\" -> \"d\";\n"));
+    assert!(!dot.contains("\"A\""));
+    assert_eq!(
+        dot,
+        scheduler
+            .to_dot()
+            .replace('A', "a")
+            .replace('B', "b")
+            .replace('C', "c")
+            .replace('D', "d")
+    );
+    assert!(lower
+        .to_markdown_table()
+        .unwrap()
+        .contains("| d | 6 | 4 | 10 |"));
+    assert!(lower.to_ndjson().unwrap().starts_with(r#"{"name":"a","#));
+
+    // the scheduler itself is untouched
+    assert!(scheduler.tasks.contains_key("A"));
+}
+
+#[test]
+fn test_to_levels_json() {
+    let json = crate::example_scheduler().to_levels_json().unwrap();
+    assert_eq!(
+        json,
+        concat!(
+            r#"[{"level":0,"tasks":[{"name":"A","duration":3}]},"#,
+            r#"{"level":1,"tasks":[{"name":"B","duration":2},{"name":"C","duration":1}]},"#,
+            r#"{"level":2,"tasks":[{"name":"D","duration":4}]}]"#
+        )
+    );
+    assert_eq!(TaskScheduler::new().to_levels_json().unwrap(), "[]");
+}
+
+#[test]
+fn test_to_jgf() {
+    let scheduler = crate::example_scheduler();
+    let jgf = scheduler.to_jgf();
+    assert_eq!(jgf.matches("\"label\"").count(), scheduler.tasks.len());
+    assert_eq!(jgf.matches("\"source\"").count(), 4);
+    assert_eq!(
+        jgf,
+        concat!(
+            r#"{"graph":{"directed":true,"nodes":{"#,
+            r#""A":{"label":"A","metadata":{"duration":3}},"#,
+            r#""B":{"label":"B","metadata":{"duration":2}},"#,
+            r#""C":{"label":"C","metadata":{"duration":1}},"#,
+            r#""D":{"label":"D","metadata":{"duration":4}}},"edges":["#,
+            r#"{"source":"A","target":"B"},{"source":"A","target":"C"},"#,
+            r#"{"source":"B","target":"D"},{"source":"C","target":"D"}]}}"#
+        )
+    );
+}
+
+#[test]
+fn test_to_plantuml_gantt() {
+    let gantt = crate::example_scheduler().to_plantuml_gantt().unwrap();
+    assert_eq!(
+        gantt,
+        concat!(
+            "@startgantt\n",
+            "[A] lasts 3 days\n",
+            "[B] lasts 2 days\n",
+            "[B] starts at [A]'s end\n",
+            "[C] lasts 1 days\n",
+            "[C] starts at [A]'s end\n",
+            // B finishes after C, so it decides when D starts
+            "[D] lasts 4 days\n",
+            "[D] starts at [B]'s end\n",
+            "@endgantt\n"
+        )
+    );
+
+    let mut scheduler = crate::example_scheduler();
+    scheduler
+        .add_task_with_lags("E", vec![("D", 2)], 1)
+        .unwrap();
+    let gantt = scheduler.to_plantuml_gantt().unwrap();
+    assert!(gantt.contains("[E] starts 2 days after [D]'s end\n"));
+}
+
+#[test]
+fn test_to_prometheus() {
+    let metrics = crate::example_scheduler().to_prometheus().unwrap();
+    let samples: Vec<&str> = metrics
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    assert_eq!(
+        samples,
+        [
+            "task_scheduled_start{task=\"A\"} 0",
+            "task_scheduled_start{task=\"B\"} 3",
+            "task_scheduled_start{task=\"C\"} 5",
+            "task_scheduled_start{task=\"D\"} 6",
+            "task_duration{task=\"A\"} 3",
+            "task_duration{task=\"B\"} 2",
+            "task_duration{task=\"C\"} 1",
+            "task_duration{task=\"D\"} 4",
+            "schedule_makespan 10",
+        ]
+    );
+    assert!(metrics.contains("# TYPE task_duration gauge\n"));
+
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("say \"hi\"\\\n", vec![], 1);
+    assert!(scheduler
+        .to_prometheus()
+        .unwrap()
+        .contains("task_duration{task=\"say \\\"hi\\\"\\\\\\n\"} 1\n"));
+}
@@ -0,0 +1,100 @@
+//! Output formats as values: implement `ScheduleFormatter` and hand it to `export`
+//! instead of adding another `to_xxx` method
+
+use std::borrow::Cow;
+
+use crate::{export::markdown_table, ScheduleError, ScheduledTask, TaskScheduler};
+
+/// Turns a schedule into text. `scheduler` is the one the schedule was computed from,
+/// for formats that also need the graph
+pub trait ScheduleFormatter {
+    fn format(&self, schedule: &[ScheduledTask], scheduler: &TaskScheduler) -> String;
+}
+
+/// `name,start,duration,finish` with a header line. Names holding a comma, quote
+/// or line break are quoted, with quotes doubled
+pub struct CsvFormatter;
+
+impl ScheduleFormatter for CsvFormatter {
+    fn format(&self, schedule: &[ScheduledTask], _: &TaskScheduler) -> String {
+        let mut out = String::from("name,start,duration,finish\n");
+        for task in schedule {
+            let name = match task.name.contains([',', '"', '\n', '\r']) {
+                true => Cow::Owned(format!("\"{}\"", task.name.replace('"', "\"\""))),
+                false => Cow::Borrowed(task.name.as_str()),
+            };
+            out.push_str(&format!(
+                "{name},{},{},{}\n",
+                task.start,
+                task.duration,
+                task.finish()
+            ));
+        }
+        out
+    }
+}
+
+/// The graph in Graphviz DOT, as `to_dot`. Only the graph is drawn, not the timing
+pub struct DotFormatter;
+
+impl ScheduleFormatter for DotFormatter {
+    fn format(&self, _: &[ScheduledTask], scheduler: &TaskScheduler) -> String {
+        scheduler.to_dot()
+    }
+}
+
+/// Markdown table like `to_markdown_table`, of the given schedule
+pub struct MarkdownFormatter;
+
+impl ScheduleFormatter for MarkdownFormatter {
+    fn format(&self, schedule: &[ScheduledTask], _: &TaskScheduler) -> String {
+        markdown_table(
+            schedule
+                .iter()
+                .map(|task| (Cow::Borrowed(task.name.as_str()), task.start, task.duration)),
+        )
+    }
+}
+
+impl TaskScheduler {
+    /// `schedule` written out by `formatter`
+    pub fn export<F: ScheduleFormatter>(&self, formatter: &F) -> Result<String, ScheduleError> {
+        Ok(formatter.format(&self.schedule()?, self))
+    }
+}
+
+#[test]
+fn test_export_custom_formatter() {
+    /// Task names in schedule order, with the task count of the graph
+    struct Names;
+
+    impl ScheduleFormatter for Names {
+        fn format(&self, schedule: &[ScheduledTask], scheduler: &TaskScheduler) -> String {
+            let names: Vec<&str> = schedule.iter().map(|task| task.name.as_str()).collect();
+            format!("{} of {}", names.join(" "), scheduler.tasks.len())
+        }
+    }
+
+    let scheduler = crate::example_scheduler();
+    assert_eq!(scheduler.export(&Names), Ok("A B C D of 4".to_string()));
+}
+
+#[test]
+fn test_builtin_formatters() {
+    let mut scheduler = crate::example_scheduler();
+    assert_eq!(
+        scheduler.export(&MarkdownFormatter),
+        scheduler.to_markdown_table()
+    );
+    assert_eq!(scheduler.export(&DotFormatter), Ok(scheduler.to_dot()));
+
+    scheduler.add_task("say \"hi\", then", vec!["D"], 1);
+    assert_eq!(
+        scheduler.export(&CsvFormatter).unwrap(),
+        concat!(
+            "name,start,duration,finish\n",
+            "A,0,3,3\nB,3,2,5\nC,5,1,6\nD,6,4,10\n",
+            "\"say \"\"hi\"\", then\",10,1,11\n"
+        )
+    );
+}
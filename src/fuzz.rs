@@ -0,0 +1,51 @@
+use crate::{Task, TaskScheduler};
+
+impl TaskScheduler {
+    /// Build a valid scheduler out of arbitrary bytes, for fuzz harnesses.
+    ///
+    /// Any input gives an acyclic graph: task `i` (named `t{i}`) only ever depends on
+    /// tasks generated before it. The first byte picks the task count (up to 63),
+    /// then each task reads its duration, a dependency count and that many dependency picks.
+    /// Running out of bytes reads zeros
+    pub fn from_fuzz_bytes(data: &[u8]) -> TaskScheduler {
+        let mut bytes = data.iter().copied();
+        let mut next = || bytes.next().unwrap_or(0);
+
+        let mut scheduler = TaskScheduler::new();
+        let count = next() % 64;
+        for i in 0..count as usize {
+            let duration = next() as u32;
+            let mut dependencies: Vec<String> = Vec::new();
+            if i > 0 {
+                for _ in 0..next() % 4 {
+                    let dep = format!("t{}", next() as usize % i);
+                    if !dependencies.contains(&dep) {
+                        dependencies.push(dep);
+                    }
+                }
+            }
+            scheduler.insert_task(Task {
+                name: format!("t{i}"),
+                dependencies,
+                duration,
+                ..Task::default()
+            });
+        }
+        scheduler
+    }
+}
+
+#[test]
+fn test_from_fuzz_bytes() {
+    let mut rng = crate::rng::SplitMix64::new(7);
+    for len in [0, 1, 5, 50, 500] {
+        let data: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+        let scheduler = TaskScheduler::from_fuzz_bytes(&data);
+        assert_eq!(
+            scheduler.schedule_tasks().unwrap().len(),
+            scheduler.tasks.len()
+        );
+        assert!(scheduler.schedule_tasks_parallel(3).is_ok());
+    }
+    assert_eq!(TaskScheduler::from_fuzz_bytes(&[3]).tasks.len(), 3);
+}
@@ -0,0 +1,1322 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{RecomputeReason, ScheduleError, TaskScheduler};
+
+/// Most paths `all_paths_to` enumerates before giving up with `GraphTooLarge`
+pub const MAX_PATHS: usize = 10_000;
+
+/// Most "already done" sets `count_orderings` explores before giving up with `GraphTooLarge`.
+/// Enough for 17 tasks that are all independent of each other
+pub const MAX_ORDERING_STATES: usize = 1 << 17;
+
+/// Fail once a walk is `depth` edges deep, past the `set_max_traversal_depth` limit
+fn check_traversal_depth(limit: Option<usize>, depth: usize) -> Result<(), ScheduleError> {
+    match limit {
+        Some(limit) if depth > limit => Err(ScheduleError::DepthLimitExceeded),
+        _ => Ok(()),
+    }
+}
+
+impl TaskScheduler {
+    /// Task names in dependency order: every task comes after all of its dependencies.
+    /// Only `dependencies` are followed, OR-groups and branches are not, so this is not
+    /// necessarily the order `schedule_tasks` (let alone a picker or priority based
+    /// schedule) runs the tasks in.
+    /// This is purely structural: timing constraints never make it fail, only cycles do
+    pub fn topological_order(&self) -> Result<Vec<String>, ScheduleError> {
+        let mut in_degree = self.first_level_dep.clone();
+        let mut queue: VecDeque<String> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        let mut order = Vec::new();
+        while let Some(name) = queue.pop_front() {
+            for neighbor in self.dependents.get(&name).into_iter().flatten() {
+                if let Some(degree) = in_degree.get_mut(neighbor) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        queue.push_back(neighbor.clone());
+                    }
+                }
+            }
+            order.push(name);
+        }
+
+        if order.len() < self.tasks.len() {
+            return Err(ScheduleError::CycleDetected);
+        }
+        Ok(order)
+    }
+
+    /// Tasks with no dependencies, sorted
+    pub fn roots(&self) -> Vec<String> {
+        let mut roots: Vec<String> = self
+            .tasks
+            .values()
+            .filter(|task| task.dependencies.is_empty())
+            .map(|task| task.name.clone())
+            .collect();
+        roots.sort();
+        roots
+    }
+
+    /// Tasks nothing else depends on, sorted
+    pub fn leaves(&self) -> Vec<String> {
+        let mut leaves: Vec<String> = self
+            .tasks
+            .keys()
+            .filter(|name| self.dependents.get(*name).is_none_or(|d| d.is_empty()))
+            .cloned()
+            .collect();
+        leaves.sort();
+        leaves
+    }
+
+    /// Tasks with no dependencies and no dependents, sorted.
+    ///
+    /// Unlike roots (which may have dependents) and leaves (which may have dependencies),
+    /// these are fully disconnected, which in a hand-maintained graph usually means
+    /// some wiring was forgotten
+    pub fn isolated_tasks(&self) -> Vec<String> {
+        let mut isolated: Vec<String> = self
+            .tasks
+            .values()
+            .filter(|task| task.dependencies.is_empty())
+            .filter(|task| self.dependents.get(&task.name).is_none_or(|d| d.is_empty()))
+            .map(|task| task.name.clone())
+            .collect();
+        isolated.sort();
+        isolated
+    }
+
+    /// Every task `name` depends on, directly or transitively.
+    /// Fails with `DepthLimitExceeded` if a dependency lies further away than
+    /// `set_max_traversal_depth` allows
+    pub fn all_dependencies(&self, name: &str) -> Result<HashSet<String>, ScheduleError> {
+        let task = self.tasks.get(name).ok_or(ScheduleError::NoTaskFound)?;
+
+        // breadth first over the dependency edges, so the depth is the shortest distance
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<(&String, usize)> =
+            task.dependencies.iter().map(|dep| (dep, 1)).collect();
+        while let Some((dep, depth)) = queue.pop_front() {
+            if !seen.insert(dep.clone()) {
+                continue;
+            }
+            check_traversal_depth(self.max_traversal_depth, depth)?;
+            if let Some(dep_task) = self.tasks.get(dep) {
+                queue.extend(dep_task.dependencies.iter().map(|next| (next, depth + 1)));
+            }
+        }
+        Ok(seen)
+    }
+
+    /// The given tasks plus everything that transitively depends on any of them.
+    /// Fails with `DepthLimitExceeded` if a dependent lies further away than
+    /// `set_max_traversal_depth` allows
+    pub fn reachable_from(&self, starts: &[&str]) -> Result<HashSet<String>, ScheduleError> {
+        if starts.iter().any(|name| !self.tasks.contains_key(*name)) {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        self.reachable_within(starts, self.max_traversal_depth)
+    }
+
+    /// `reachable_from` with an explicit depth limit, the starts must exist
+    fn reachable_within(
+        &self,
+        starts: &[&str],
+        limit: Option<usize>,
+    ) -> Result<HashSet<String>, ScheduleError> {
+        // multi-source breadth first search over the reverse-dependency edges
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut queue: VecDeque<(String, usize)> =
+            starts.iter().map(|name| (name.to_string(), 0)).collect();
+        while let Some((name, depth)) = queue.pop_front() {
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            check_traversal_depth(limit, depth)?;
+            if let Some(dependents) = self.dependents.get(&name) {
+                queue.extend(
+                    dependents
+                        .iter()
+                        .filter(|d| !seen.contains(*d))
+                        .map(|d| (d.clone(), depth + 1)),
+                );
+            }
+        }
+        Ok(seen)
+    }
+
+    /// Limit how many edges `all_dependencies`, `reachable_from` and `all_dependents` follow
+    /// before giving up with `DepthLimitExceeded`. `None`, the default, walks any depth.
+    /// The walks never recurse, so this guards against runaway graphs and not the stack
+    pub fn set_max_traversal_depth(&mut self, depth: Option<usize>) {
+        self.max_traversal_depth = depth;
+    }
+
+    /// Every task that depends on `name`, directly or transitively
+    pub fn all_dependents(&self, name: &str) -> Result<HashSet<String>, ScheduleError> {
+        let mut reachable = self.reachable_from(&[name])?;
+        reachable.remove(name);
+        Ok(reachable)
+    }
+
+    /// `all_dependents` of every task at once, for answering many of those queries on a graph
+    /// that doesn't change. Built in one pass over the reverse topological order, but it holds
+    /// up to V² names: a long chain already stores V²/2 of them
+    pub fn transitive_closure(&self) -> Result<HashMap<String, HashSet<String>>, ScheduleError> {
+        let mut closure: HashMap<String, HashSet<String>> = HashMap::new();
+        for name in self.topological_order()?.into_iter().rev() {
+            let mut reachable = HashSet::new();
+            for dependent in self.dependents.get(&name).into_iter().flatten() {
+                reachable.insert(dependent.clone());
+                if let Some(further) = closure.get(dependent) {
+                    reachable.extend(further.iter().cloned());
+                }
+            }
+            closure.insert(name, reachable);
+        }
+        Ok(closure)
+    }
+
+    /// Tasks not reachable from any root (a task with no dependencies) by following dependents.
+    ///
+    /// Always empty for an acyclic graph; otherwise lists the tasks that are part of,
+    /// or wait on, a cycle detached from the roots. A task waiting on a dependency that
+    /// was never added is reported as well. Sorted by name.
+    /// This is one linear pass, so it ignores `set_max_traversal_depth`
+    pub fn unreachable_tasks(&self) -> Vec<String> {
+        let roots = self.roots();
+        let roots: Vec<&str> = roots.iter().map(String::as_str).collect();
+        let reachable = self
+            .reachable_within(&roots, None)
+            .expect("roots exist and there is no depth limit");
+
+        let mut unreachable: Vec<String> = self
+            .tasks
+            .keys()
+            .filter(|name| !reachable.contains(*name))
+            .cloned()
+            .collect();
+        unreachable.sort();
+        unreachable
+    }
+
+    /// Fail with `OrphanTask` naming the first of `unreachable_tasks`, if there are any.
+    /// A gate for pipelines where every task must be wired into the main flow
+    pub fn require_all_reachable(&self) -> Result<(), ScheduleError> {
+        match self.unreachable_tasks().into_iter().next() {
+            Some(orphan) => Err(ScheduleError::OrphanTask(orphan)),
+            None => Ok(()),
+        }
+    }
+
+    /// Tasks that can't start yet solely because the in-progress task `name` hasn't finished.
+    ///
+    /// Since `name` is running, everything it (transitively) depends on is known to be complete;
+    /// nothing else is assumed complete. So this returns the direct dependents of `name` whose
+    /// other dependencies are all ancestors of `name`, sorted by name
+    pub fn blocked_by(&self, name: &str) -> Result<Vec<String>, ScheduleError> {
+        let completed = self.all_dependencies(name)?;
+
+        let mut blocked: Vec<String> = self
+            .dependents
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|dependent| {
+                self.tasks.get(*dependent).is_some_and(|task| {
+                    task.dependencies
+                        .iter()
+                        .all(|dep| dep == name || completed.contains(dep))
+                })
+            })
+            .cloned()
+            .collect();
+        blocked.sort();
+        blocked.dedup();
+        Ok(blocked)
+    }
+
+    /// Tasks that become ready if `name` finishes now, with the tasks in `completed` done:
+    /// the dependents of `name` whose other dependencies are all in `completed`, sorted.
+    /// Dependents already in `completed` are left out
+    pub fn would_unblock(
+        &self,
+        completed: &HashSet<String>,
+        name: &str,
+    ) -> Result<Vec<String>, ScheduleError> {
+        if !self.tasks.contains_key(name) {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        let mut unblocked: Vec<String> = self
+            .dependents
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|dependent| !completed.contains(*dependent))
+            .filter(|dependent| {
+                self.tasks.get(*dependent).is_some_and(|task| {
+                    task.dependencies
+                        .iter()
+                        .all(|dep| dep == name || completed.contains(dep))
+                })
+            })
+            .cloned()
+            .collect();
+        unblocked.sort();
+        unblocked.dedup();
+        Ok(unblocked)
+    }
+
+    /// Every simple path from a root (a task without dependencies) to `name`,
+    /// following dependency edges. Each path starts at the root and ends at `name`,
+    /// and the paths are sorted.
+    ///
+    /// Dense graphs can have exponentially many paths, so more than `MAX_PATHS`
+    /// is `GraphTooLarge`. Dependencies that were never added are not followed
+    pub fn all_paths_to(&self, name: &str) -> Result<Vec<Vec<String>>, ScheduleError> {
+        if !self.tasks.contains_key(name) {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        let mut paths: Vec<Vec<String>> = Vec::new();
+
+        // partial paths walking back from `name`, so they are stored reversed
+        let mut stack: Vec<Vec<&String>> = vec![vec![&self.tasks[name].name]];
+        while let Some(path) = stack.pop() {
+            let last = path[path.len() - 1];
+            let task = &self.tasks[last];
+            if task.dependencies.is_empty() {
+                if paths.len() == MAX_PATHS {
+                    return Err(ScheduleError::GraphTooLarge);
+                }
+                paths.push(path.iter().rev().map(|n| n.to_string()).collect());
+                continue;
+            }
+            for dep in &task.dependencies {
+                if self.tasks.contains_key(dep) && !path.contains(&dep) {
+                    let mut longer = path.clone();
+                    longer.push(dep);
+                    stack.push(longer);
+                }
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Groups of two or more tasks with the same dependencies, the same dependents and the
+    /// same duration. Such tasks are interchangeable, which often means one was added twice.
+    /// Each group is sorted, and so is the list of groups
+    pub fn equivalent_task_groups(&self) -> Vec<Vec<String>> {
+        // keyed by (sorted dependencies, sorted dependents, duration)
+        type Shape<'a> = (Vec<&'a String>, Vec<&'a String>, u32);
+        let mut groups: HashMap<Shape, Vec<String>> = HashMap::new();
+        for task in self.tasks.values() {
+            let mut dependencies: Vec<&String> = task.dependencies.iter().collect();
+            dependencies.sort();
+            let mut dependents: Vec<&String> = self
+                .dependents
+                .get(&task.name)
+                .into_iter()
+                .flatten()
+                .collect();
+            dependents.sort();
+            groups
+                .entry((dependencies, dependents, task.duration))
+                .or_default()
+                .push(task.name.clone());
+        }
+
+        let mut groups: Vec<Vec<String>> = groups
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .map(|mut group| {
+                group.sort();
+                group
+            })
+            .collect();
+        groups.sort();
+        groups
+    }
+
+    /// Length of the longest dependency chain leading to each task: 0 for roots,
+    /// otherwise one more than the deepest dependency
+    pub fn depths(&self) -> Result<HashMap<String, u32>, ScheduleError> {
+        let mut depths: HashMap<String, u32> = HashMap::new();
+        for name in self.topological_order()? {
+            let depth = self.tasks[&name]
+                .dependencies
+                .iter()
+                .filter_map(|dep| depths.get(dep))
+                .map(|depth| depth + 1)
+                .max()
+                .unwrap_or(0);
+            depths.insert(name, depth);
+        }
+        Ok(depths)
+    }
+
+    /// A largest set of tasks where no task depends (even transitively) on another, sorted.
+    /// Its size is the most tasks that could ever run at the same time.
+    ///
+    /// Computed exactly through Dilworth's theorem: a maximum matching over the
+    /// "is an ancestor of" relation gives a minimum chain cover, and the antichain
+    /// is read off the matching's minimum vertex cover (König's theorem).
+    /// This can be wider than any single layer of `schedule_levels`
+    pub fn max_antichain(&self) -> Result<Vec<String>, ScheduleError> {
+        let ancestors = self.ancestor_sets()?;
+        let mut names: Vec<&String> = self.tasks.keys().collect();
+        names.sort();
+        let index: HashMap<&String, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (*name, i))
+            .collect();
+
+        // left copy of u -> right copy of every v that u is an ancestor of
+        let mut later: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+        for (v, name) in names.iter().enumerate() {
+            for ancestor in &ancestors[*name] {
+                if let Some(&u) = index.get(ancestor) {
+                    later[u].push(v);
+                }
+            }
+        }
+
+        // maximum bipartite matching with one BFS augmenting path search per left vertex
+        let mut match_of_right: Vec<Option<usize>> = vec![None; names.len()];
+        let mut match_of_left: Vec<Option<usize>> = vec![None; names.len()];
+        for root in 0..names.len() {
+            // right vertex -> left vertex it was reached from
+            let mut reached_from: Vec<Option<usize>> = vec![None; names.len()];
+            let mut queue = VecDeque::from([root]);
+            let mut free_right = None;
+            'search: while let Some(u) = queue.pop_front() {
+                for &v in &later[u] {
+                    if reached_from[v].is_some() {
+                        continue;
+                    }
+                    reached_from[v] = Some(u);
+                    match match_of_right[v] {
+                        Some(next) => queue.push_back(next),
+                        None => {
+                            free_right = Some(v);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+            // flip the edges along the path back to `root`
+            let mut v = free_right;
+            while let Some(right) = v {
+                let left = reached_from[right].expect("on the augmenting path");
+                v = match_of_left[left];
+                match_of_left[left] = Some(right);
+                match_of_right[right] = Some(left);
+            }
+        }
+
+        // alternating reachability from the unmatched left vertices
+        let mut left_seen = vec![false; names.len()];
+        let mut right_seen = vec![false; names.len()];
+        let mut queue: VecDeque<usize> = (0..names.len())
+            .filter(|&u| match_of_left[u].is_none())
+            .collect();
+        for &u in &queue {
+            left_seen[u] = true;
+        }
+        while let Some(u) = queue.pop_front() {
+            for &v in &later[u] {
+                if right_seen[v] || match_of_left[u] == Some(v) {
+                    continue;
+                }
+                right_seen[v] = true;
+                if let Some(next) = match_of_right[v] {
+                    if !left_seen[next] {
+                        left_seen[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        // tasks with neither copy in the vertex cover (unseen left, seen right)
+        Ok((0..names.len())
+            .filter(|&i| left_seen[i] && !right_seen[i])
+            .map(|i| names[i].clone())
+            .collect())
+    }
+
+    /// Weakly connected components: groups of tasks linked by edges in either direction.
+    /// Each component is sorted, and components are ordered by their first name.
+    /// Dependencies that were never added are not part of any component
+    pub fn connected_components(&self) -> Vec<Vec<String>> {
+        let mut names: Vec<&String> = self.tasks.keys().collect();
+        names.sort();
+
+        let mut seen: HashSet<&String> = HashSet::new();
+        let mut components = Vec::new();
+        for name in names {
+            if !seen.insert(name) {
+                continue;
+            }
+            let mut component = Vec::new();
+            let mut stack = vec![name];
+            while let Some(current) = stack.pop() {
+                component.push(current.clone());
+                let deps = self.tasks[current].dependencies.iter();
+                let dependents = self.dependents.get(current).into_iter().flatten();
+                for next in deps.chain(dependents) {
+                    if let Some((next, _)) = self.tasks.get_key_value(next) {
+                        if seen.insert(next) {
+                            stack.push(next);
+                        }
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        components
+    }
+
+    /// Cut vertices of the graph with edges taken as undirected: tasks whose removal splits
+    /// their weakly connected component in two or more. Sorted.
+    ///
+    /// Iterative Tarjan low-link search. Dependencies that were never added are ignored
+    pub fn articulation_tasks(&self) -> Vec<String> {
+        let mut names: Vec<&String> = self.tasks.keys().collect();
+        names.sort();
+        let index: HashMap<&String, usize> =
+            names.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+        let neighbors: Vec<Vec<usize>> = names
+            .iter()
+            .map(|&name| {
+                let deps = self.tasks[name].dependencies.iter();
+                let dependents = self.dependents.get(name).into_iter().flatten();
+                let mut next: Vec<usize> = deps
+                    .chain(dependents)
+                    .filter_map(|next| index.get(next).copied())
+                    .filter(|&next| next != index[name])
+                    .collect();
+                next.sort();
+                next.dedup();
+                next
+            })
+            .collect();
+
+        let mut discovered: Vec<Option<usize>> = vec![None; names.len()];
+        let mut low = vec![0; names.len()];
+        let mut cut = vec![false; names.len()];
+        let mut clock = 0;
+        for root in 0..names.len() {
+            if discovered[root].is_some() {
+                continue;
+            }
+            discovered[root] = Some(clock);
+            low[root] = clock;
+            clock += 1;
+            let mut children = 0;
+            // (task, its parent, next neighbor to look at)
+            let mut stack: Vec<(usize, Option<usize>, usize)> = vec![(root, None, 0)];
+            while let Some(&mut (task, parent, ref mut cursor)) = stack.last_mut() {
+                if let Some(&next) = neighbors[task].get(*cursor) {
+                    *cursor += 1;
+                    match discovered[next] {
+                        Some(seen) => {
+                            if Some(next) != parent {
+                                low[task] = low[task].min(seen);
+                            }
+                        }
+                        None => {
+                            discovered[next] = Some(clock);
+                            low[next] = clock;
+                            clock += 1;
+                            if task == root {
+                                children += 1;
+                            }
+                            stack.push((next, Some(task), 0));
+                        }
+                    }
+                    continue;
+                }
+                stack.pop();
+                if let Some(parent) = parent {
+                    low[parent] = low[parent].min(low[task]);
+                    let reached = discovered[parent].expect("parent was discovered first");
+                    if parent != root && low[task] >= reached {
+                        cut[parent] = true;
+                    }
+                }
+            }
+            cut[root] = children > 1;
+        }
+
+        names
+            .into_iter()
+            .zip(cut)
+            .filter(|&(_, cut)| cut)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Tasks whose removal leaves the graph without cycles, sorted. Empty for an acyclic graph.
+    ///
+    /// Greedy, so the set is small but not necessarily the smallest (that problem is NP-hard):
+    /// tasks that can't be on a cycle (nothing left before or after them) are peeled off,
+    /// then the task with the most `dependencies x dependents` among the rest is taken,
+    /// until nothing is left. Dependencies that were never added are ignored
+    pub fn feedback_task_set(&self) -> Vec<String> {
+        let mut names: Vec<&String> = self.tasks.keys().collect();
+        names.sort();
+        let index: HashMap<&String, usize> =
+            names.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+        let mut preds: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+        let mut succs: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+        for (i, name) in names.iter().enumerate() {
+            let deps: HashSet<usize> = self.tasks[*name]
+                .dependencies
+                .iter()
+                .filter_map(|dep| index.get(dep).copied())
+                .collect();
+            for dep in deps {
+                preds[i].push(dep);
+                succs[dep].push(i);
+            }
+        }
+
+        let mut alive = vec![true; names.len()];
+        let mut in_degree: Vec<usize> = preds.iter().map(Vec::len).collect();
+        let mut out_degree: Vec<usize> = succs.iter().map(Vec::len).collect();
+        let mut removed = Vec::new();
+        let mut queue: VecDeque<usize> = (0..names.len())
+            .filter(|&i| in_degree[i] == 0 || out_degree[i] == 0)
+            .collect();
+        loop {
+            while let Some(i) = queue.pop_front() {
+                if !alive[i] {
+                    continue;
+                }
+                alive[i] = false;
+                for &next in &succs[i] {
+                    in_degree[next] -= 1;
+                    if alive[next] && in_degree[next] == 0 {
+                        queue.push_back(next);
+                    }
+                }
+                for &prev in &preds[i] {
+                    out_degree[prev] -= 1;
+                    if alive[prev] && out_degree[prev] == 0 {
+                        queue.push_back(prev);
+                    }
+                }
+            }
+            // `max_by_key` keeps the last maximum, so walk the tasks in reverse
+            let Some(pick) = (0..names.len())
+                .rev()
+                .filter(|&i| alive[i])
+                .max_by_key(|&i| in_degree[i] * out_degree[i])
+            else {
+                break;
+            };
+            removed.push(names[pick].clone());
+            queue.push_back(pick);
+        }
+        removed.sort();
+        removed
+    }
+
+    /// Each weakly connected component's tasks in dependency order,
+    /// components ordered as in `connected_components`
+    pub fn ordered_components(&self) -> Result<Vec<Vec<String>>, ScheduleError> {
+        let components = self.connected_components();
+        let component_of: HashMap<&String, usize> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, names)| names.iter().map(move |name| (name, i)))
+            .collect();
+
+        let mut ordered = vec![Vec::new(); components.len()];
+        for name in self.topological_order()? {
+            ordered[component_of[&name]].push(name);
+        }
+        Ok(ordered)
+    }
+
+    /// Whether the graph is a forest: no task has more than one dependent, and there is no cycle
+    pub fn is_forest(&self) -> bool {
+        self.dependents
+            .values()
+            .all(|dependents| dependents.len() <= 1)
+            && self.topological_order().is_ok()
+    }
+
+    /// Number of distinct valid topological orderings.
+    ///
+    /// A fully constrained graph (e.g a chain) has exactly one.
+    /// This walks every "already done" set reachable while running tasks in dependency order,
+    /// which is exponential in the width of the graph: `n` independent tasks make 2^n sets.
+    /// More than `MAX_ORDERING_STATES` sets, or more than 128 tasks, return `GraphTooLarge`.
+    /// A count that doesn't fit in `u128` saturates at `u128::MAX`
+    pub fn count_orderings(&self) -> Result<u128, ScheduleError> {
+        let order = self.topological_order()?;
+        if order.len() > 128 {
+            return Err(ScheduleError::GraphTooLarge);
+        }
+        let index: HashMap<&String, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name, i))
+            .collect();
+        let required: Vec<u128> = order
+            .iter()
+            .map(|name| {
+                self.tasks[name]
+                    .dependencies
+                    .iter()
+                    .filter_map(|dep| index.get(dep))
+                    .fold(0u128, |mask, &i| mask | 1 << i)
+            })
+            .collect();
+
+        // ways[done] = number of orderings that complete exactly the `done` tasks first.
+        // Every step completes one more task, so go layer by layer
+        let mut layer: HashMap<u128, u128> = HashMap::from([(0, 1)]);
+        let mut explored = 1;
+        for _ in 0..order.len() {
+            let mut next: HashMap<u128, u128> = HashMap::new();
+            for (&done, &ways) in &layer {
+                for (i, &needs) in required.iter().enumerate() {
+                    let bit = 1u128 << i;
+                    if done & bit == 0 && needs & done == needs {
+                        let entry = next.entry(done | bit).or_insert(0);
+                        *entry = entry.saturating_add(ways);
+                    }
+                }
+                if explored + next.len() > MAX_ORDERING_STATES {
+                    return Err(ScheduleError::GraphTooLarge);
+                }
+            }
+            explored += next.len();
+            layer = next;
+        }
+        Ok(layer
+            .values()
+            .copied()
+            .fold(0u128, u128::saturating_add)
+            .max(1))
+    }
+
+    /// Tasks grouped into topological levels: level 0 are the roots, and every other task
+    /// sits one level below its deepest dependency. Tasks within a level are sorted by name
+    /// and never depend on each other
+    pub fn schedule_levels(&self) -> Result<Vec<Vec<String>>, ScheduleError> {
+        let mut in_degree = self.first_level_dep.clone();
+        let mut current: Vec<String> = self.roots();
+        let mut levels = Vec::new();
+        let mut placed = 0;
+
+        // peel off one layer of zero in-degree tasks at a time
+        while !current.is_empty() {
+            let mut next = Vec::new();
+            for name in &current {
+                for neighbor in self.dependents.get(name).into_iter().flatten() {
+                    if let Some(degree) = in_degree.get_mut(neighbor) {
+                        *degree -= 1;
+                        if *degree == 0 {
+                            next.push(neighbor.clone());
+                        }
+                    }
+                }
+            }
+            next.sort();
+            placed += current.len();
+            levels.push(current);
+            current = next;
+        }
+
+        if placed < self.tasks.len() {
+            return Err(ScheduleError::CycleDetected);
+        }
+        Ok(levels)
+    }
+
+    /// Size of the largest topological level: the most parallelism the graph structure
+    /// allows, ignoring durations
+    pub fn peak_level_width(&self) -> Result<usize, ScheduleError> {
+        Ok(self
+            .schedule_levels()?
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0))
+    }
+
+    /// For every task, the set of tasks it depends on directly or transitively.
+    /// Built in topological order so each dependency's set is complete before it is used
+    pub(crate) fn ancestor_sets(&self) -> Result<HashMap<String, HashSet<String>>, ScheduleError> {
+        let mut ancestors: HashMap<String, HashSet<String>> = HashMap::new();
+        for name in self.topological_order()? {
+            let mut set = HashSet::new();
+            for dep in &self.tasks[&name].dependencies {
+                set.insert(dep.clone());
+                if let Some(dep_ancestors) = ancestors.get(dep) {
+                    set.extend(dep_ancestors.iter().cloned());
+                }
+            }
+            ancestors.insert(name, set);
+        }
+        Ok(ancestors)
+    }
+
+    /// Direct dependency edges that are already implied through another path.
+    ///
+    /// Returned as `(task, dependency)` pairs, sorted.
+    /// e.g. if C depends on both A and B, and B depends on A,
+    /// then `(C, A)` is redundant since waiting on B already means waiting on A.
+    /// An edge with a lag only counts when the other path holds the task back at least
+    /// as long, durations and lags included
+    pub fn redundant_edges(&self) -> Result<Vec<(String, String)>, ScheduleError> {
+        let ancestors = self.ancestor_sets()?;
+        let order = self.topological_order()?;
+        let mut redundant = Vec::new();
+
+        for (name, task) in &self.tasks {
+            for dep in &task.dependencies {
+                // the edge is implied if some other direct dependency
+                // already (transitively) depends on `dep`
+                let mut others = task
+                    .dependencies
+                    .iter()
+                    .filter(|other| *other != dep && ancestors[*other].contains(dep));
+                let lag = task.lag(dep);
+                let implied = if lag == 0 {
+                    others.next().is_some()
+                } else {
+                    let after = self.longest_after(&order, dep);
+                    others.any(|other| after[other.as_str()] + task.lag(other) as u64 >= lag as u64)
+                };
+                if implied {
+                    redundant.push((name.clone(), dep.clone()));
+                }
+            }
+        }
+
+        redundant.sort();
+        Ok(redundant)
+    }
+
+    /// Longest time from `start` finishing to each task after it finishing, following
+    /// dependency edges with their lags. `order` is a topological order
+    fn longest_after<'a>(&'a self, order: &'a [String], start: &str) -> HashMap<&'a str, u64> {
+        let mut after: HashMap<&str, u64> = HashMap::new();
+        for name in order.iter().skip_while(|name| *name != start) {
+            let task = &self.tasks[name];
+            let longest = if name == start {
+                Some(0)
+            } else {
+                task.dependencies
+                    .iter()
+                    .filter_map(|dep| Some(after.get(dep.as_str())? + task.lag(dep) as u64))
+                    .max()
+            };
+            if let Some(longest) = longest {
+                let extra = if name == start {
+                    0
+                } else {
+                    task.duration as u64
+                };
+                after.insert(name, longest + extra);
+            }
+        }
+        after
+    }
+
+    /// Drop one direct edge "`task` depends on `dep`", keeping the derived maps in sync.
+    /// The lag on `dep` goes along with its last edge
+    pub(crate) fn remove_edge(&mut self, task: &str, dep: &str) {
+        let Some(t) = self.tasks.get_mut(task) else {
+            return;
+        };
+        let Some(index) = t.dependencies.iter().position(|d| d == dep) else {
+            return;
+        };
+        t.dependencies.remove(index);
+        if !t.dependencies.iter().any(|d| d == dep) {
+            t.lags.remove(dep);
+        }
+        if let Some(degree) = self.first_level_dep.get_mut(task) {
+            *degree = degree.saturating_sub(1);
+        }
+        if let Some(dependents) = self.dependents.get_mut(dep) {
+            if let Some(index) = dependents.iter().position(|d| d == task) {
+                dependents.remove(index);
+            }
+            if dependents.is_empty() {
+                self.dependents.remove(dep);
+            }
+        }
+        self.record_task(task);
+        self.notify(RecomputeReason::DependenciesChanged);
+    }
+
+    /// Remove every edge reported by `redundant_edges`.
+    /// Reachability is unchanged and lagged edges are only dropped when another path
+    /// covers the lag, so the graph still produces the same order.
+    /// Returns the number of edges removed
+    pub fn transitive_reduction(&mut self) -> Result<usize, ScheduleError> {
+        let redundant = self.redundant_edges()?;
+        for (task, dep) in &redundant {
+            self.remove_edge(task, dep);
+        }
+        Ok(redundant.len())
+    }
+}
+
+#[test]
+fn test_redundant_edges() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 1);
+    scheduler.add_task("B", vec!["A"], 1);
+    scheduler.add_task("C", vec!["B", "A"], 1);
+
+    assert_eq!(
+        scheduler.redundant_edges(),
+        Ok(vec![("C".to_string(), "A".to_string())])
+    );
+
+    // the diamond has no shortcut edges
+    assert_eq!(crate::example_scheduler().redundant_edges(), Ok(vec![]));
+}
+
+#[test]
+fn test_transitive_reduction() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 3);
+    scheduler.add_task("B", vec!["A"], 2);
+    scheduler.add_task("C", vec!["B", "A"], 1);
+    scheduler.add_task("D", vec!["C", "A", "B"], 4);
+
+    let edge_count =
+        |s: &TaskScheduler| -> usize { s.tasks.values().map(|t| t.dependencies.len()).sum() };
+    let before = scheduler.schedule_tasks();
+    assert_eq!(edge_count(&scheduler), 6);
+
+    // C -> A, D -> A and D -> B are all implied by the A -> B -> C -> D chain
+    assert_eq!(scheduler.transitive_reduction(), Ok(3));
+    assert_eq!(edge_count(&scheduler), 3);
+    assert_eq!(scheduler.first_level_dep["D"], 1);
+    assert_eq!(scheduler.dependents["A"], vec!["B".to_string()]);
+    assert_eq!(scheduler.schedule_tasks(), before);
+    assert_eq!(scheduler.redundant_edges(), Ok(vec![]));
+}
+
+#[test]
+fn test_transitive_reduction_keeps_lags() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("a", vec![], 1);
+    scheduler.add_task("b", vec!["a"], 1);
+    scheduler
+        .add_task_with_lags("c", vec![("a", 10), ("b", 0)], 1)
+        .unwrap();
+    let before = scheduler.schedule_tasks().unwrap();
+    assert_eq!(before[2], ("c".to_string(), 11, 1));
+
+    // a -> b -> c only holds c back by 1, not 10
+    assert_eq!(scheduler.transitive_reduction(), Ok(0));
+    assert_eq!(scheduler.schedule_tasks().unwrap(), before);
+
+    // a lag the other path covers is implied
+    scheduler
+        .tasks
+        .get_mut("c")
+        .unwrap()
+        .lags
+        .insert("a".to_string(), 1);
+    assert_eq!(scheduler.transitive_reduction(), Ok(1));
+    assert_eq!(scheduler.tasks["c"].dependencies, ["b"]);
+    assert!(scheduler.tasks["c"].lags.is_empty());
+}
+
+#[test]
+fn test_remove_edge_once() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("a", vec![], 1);
+    scheduler
+        .add_task_with_lags("b", vec![("a", 2), ("a", 2)], 1)
+        .unwrap();
+    assert_eq!(scheduler.first_level_dep["b"], 2);
+
+    scheduler.remove_edge("b", "a");
+    assert_eq!(scheduler.tasks["b"].dependencies, ["a"]);
+    assert_eq!(scheduler.first_level_dep["b"], 1);
+    assert_eq!(scheduler.tasks["b"].lag("a"), 2);
+
+    scheduler.remove_edge("b", "a");
+    assert!(scheduler.tasks["b"].dependencies.is_empty());
+    assert_eq!(scheduler.first_level_dep["b"], 0);
+    assert!(scheduler.tasks["b"].lags.is_empty());
+    assert!(!scheduler.dependents.contains_key("a"));
+}
+
+#[test]
+fn test_blocked_by() {
+    let scheduler = crate::example_scheduler();
+
+    // while A runs, B and C wait only on A
+    assert_eq!(
+        scheduler.blocked_by("A"),
+        Ok(vec!["B".to_string(), "C".to_string()])
+    );
+    // D also needs C, which may not have run yet while B runs
+    assert_eq!(scheduler.blocked_by("B"), Ok(vec![]));
+    assert_eq!(scheduler.blocked_by("X"), Err(ScheduleError::NoTaskFound));
+}
+
+#[test]
+fn test_roots_and_leaves() {
+    let scheduler = crate::example_scheduler();
+    assert_eq!(scheduler.roots(), vec!["A"]);
+    assert_eq!(scheduler.leaves(), vec!["D"]);
+}
+
+#[test]
+fn test_count_orderings() {
+    let mut chain = TaskScheduler::new();
+    chain.add_task("A", vec![], 1);
+    chain.add_task("B", vec!["A"], 1);
+    chain.add_task("C", vec!["B"], 1);
+    chain.add_task("D", vec!["C"], 1);
+    assert_eq!(chain.count_orderings(), Ok(1));
+
+    // B and C can go in either order
+    assert_eq!(crate::example_scheduler().count_orderings(), Ok(2));
+
+    // four independent tasks: 4!
+    let mut flat = TaskScheduler::new();
+    for name in ["A", "B", "C", "D"] {
+        flat.add_task(name, vec![], 1);
+    }
+    assert_eq!(flat.count_orderings(), Ok(24));
+
+    // 2^17 done-sets fit, 2^18 don't
+    let mut wide = TaskScheduler::new();
+    for i in 0..17 {
+        wide.add_task(&format!("t{i}"), vec![], 1);
+    }
+    assert_eq!(wide.count_orderings(), Ok((1..=17).product()));
+    wide.add_task("t17", vec![], 1);
+    assert_eq!(wide.count_orderings(), Err(ScheduleError::GraphTooLarge));
+
+    let mut wider = TaskScheduler::new();
+    for i in 0..40 {
+        wider.add_task(&format!("t{i}"), vec![], 1);
+    }
+    assert_eq!(wider.count_orderings(), Err(ScheduleError::GraphTooLarge));
+}
+
+#[test]
+fn test_reachable_from() {
+    let scheduler = crate::example_scheduler();
+    let set = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<HashSet<_>>();
+
+    assert_eq!(scheduler.reachable_from(&["B"]), Ok(set(&["B", "D"])));
+    assert_eq!(
+        scheduler.reachable_from(&["B", "C"]),
+        Ok(set(&["B", "C", "D"]))
+    );
+    assert_eq!(
+        scheduler.reachable_from(&["B", "X"]),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
+
+#[test]
+fn test_isolated_tasks() {
+    let mut scheduler = crate::example_scheduler();
+    assert!(scheduler.isolated_tasks().is_empty());
+
+    scheduler.add_task("Lonely", vec![], 1);
+    assert_eq!(scheduler.isolated_tasks(), vec!["Lonely"]);
+}
+
+#[test]
+fn test_schedule_levels() {
+    let scheduler = crate::example_scheduler();
+    assert_eq!(
+        scheduler.schedule_levels(),
+        Ok(vec![
+            vec!["A".to_string()],
+            vec!["B".to_string(), "C".to_string()],
+            vec!["D".to_string()],
+        ])
+    );
+    assert_eq!(scheduler.peak_level_width(), Ok(2));
+}
+
+#[test]
+fn test_unreachable_tasks() {
+    let mut scheduler = crate::example_scheduler();
+    assert!(scheduler.unreachable_tasks().is_empty());
+
+    // a detached X <-> Y cycle, plus Z hanging off it
+    scheduler.add_task("X", vec!["Y"], 1);
+    scheduler.add_task("Y", vec!["X"], 1);
+    scheduler.add_task("Z", vec!["X"], 1);
+    assert_eq!(scheduler.unreachable_tasks(), vec!["X", "Y", "Z"]);
+}
+
+#[test]
+fn test_all_paths_to() {
+    let scheduler = crate::example_scheduler();
+    assert_eq!(
+        scheduler.all_paths_to("D"),
+        Ok(vec![
+            vec!["A".to_string(), "B".into(), "D".into()],
+            vec!["A".to_string(), "C".into(), "D".into()],
+        ])
+    );
+    assert_eq!(scheduler.all_paths_to("A"), Ok(vec![vec!["A".to_string()]]));
+    assert_eq!(scheduler.all_paths_to("X"), Err(ScheduleError::NoTaskFound));
+
+    // 15 layers of two tasks, each fully connected to the previous one: 2^14 paths
+    let mut dense = TaskScheduler::new();
+    let names = |layer: usize| [format!("{layer}a"), format!("{layer}b")];
+    for layer in 0..15 {
+        let deps = if layer == 0 {
+            vec![]
+        } else {
+            names(layer - 1).to_vec()
+        };
+        for name in names(layer) {
+            dense.add_task(&name, deps.iter().map(String::as_str).collect(), 1);
+        }
+    }
+    assert_eq!(dense.all_paths_to("14a"), Err(ScheduleError::GraphTooLarge));
+}
+
+#[test]
+fn test_equivalent_task_groups() {
+    let mut scheduler = crate::example_scheduler();
+    // B and C differ only in duration
+    assert!(scheduler.equivalent_task_groups().is_empty());
+
+    scheduler.set_duration("C", 2).unwrap();
+    assert_eq!(scheduler.equivalent_task_groups(), vec![vec!["B", "C"]]);
+}
+
+#[test]
+fn test_is_forest() {
+    let mut chain = TaskScheduler::new();
+    chain.add_task("A", vec![], 1);
+    chain.add_task("B", vec!["A"], 1);
+    chain.add_task("C", vec!["B"], 1);
+    assert!(chain.is_forest());
+
+    // A feeds both B and C
+    assert!(!crate::example_scheduler().is_forest());
+
+    chain.add_task("D", vec!["D"], 1);
+    assert!(!chain.is_forest());
+}
+
+#[test]
+fn test_depths() {
+    let mut scheduler = crate::example_scheduler();
+    let depths = scheduler.depths().unwrap();
+    assert_eq!(depths.len(), 4);
+    for (name, depth) in [("A", 0), ("B", 1), ("C", 1), ("D", 2)] {
+        assert_eq!(depths[name], depth);
+    }
+
+    scheduler.add_task("X", vec!["X"], 1);
+    assert_eq!(scheduler.depths(), Err(ScheduleError::CycleDetected));
+}
+
+#[test]
+fn test_max_antichain() {
+    assert_eq!(
+        crate::example_scheduler().max_antichain(),
+        Ok(vec!["B".to_string(), "C".to_string()])
+    );
+
+    // levels are [R, X], [M], [C1, C2]; but X is unrelated to C1 and C2
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("R", vec![], 1);
+    scheduler.add_task("M", vec!["R"], 1);
+    scheduler.add_task("C1", vec!["M"], 1);
+    scheduler.add_task("C2", vec!["M"], 1);
+    scheduler.add_task("X", vec![], 1);
+    assert_eq!(scheduler.peak_level_width(), Ok(2));
+    assert_eq!(
+        scheduler.max_antichain(),
+        Ok(vec!["C1".to_string(), "C2".to_string(), "X".to_string()])
+    );
+}
+
+#[test]
+fn test_connected_components() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("b2", vec!["b1"], 1);
+    scheduler.add_task("b1", vec![], 1);
+    scheduler.add_task("a3", vec!["a2"], 1);
+    scheduler.add_task("a2", vec!["a1"], 1);
+    scheduler.add_task("a1", vec![], 1);
+
+    assert_eq!(
+        scheduler.connected_components(),
+        vec![vec!["a1", "a2", "a3"], vec!["b1", "b2"]]
+    );
+    assert_eq!(
+        scheduler.ordered_components(),
+        Ok(vec![
+            vec!["a1".to_string(), "a2".into(), "a3".into()],
+            vec!["b1".to_string(), "b2".into()],
+        ])
+    );
+}
+
+#[test]
+fn test_require_all_reachable() {
+    let mut scheduler = crate::example_scheduler();
+    assert_eq!(scheduler.require_all_reachable(), Ok(()));
+
+    // waits on a task that was never added, so no root leads to it
+    scheduler.add_task("X", vec!["missing"], 1);
+    assert_eq!(
+        scheduler.require_all_reachable(),
+        Err(ScheduleError::OrphanTask("X".to_string()))
+    );
+}
+
+#[test]
+fn test_articulation_tasks() {
+    // B and C are two routes from A to D, so losing any single task keeps the rest together
+    assert!(crate::example_scheduler().articulation_tasks().is_empty());
+
+    let mut scheduler = crate::example_scheduler();
+    scheduler.add_task("E", vec!["D"], 1);
+    scheduler.add_task("F", vec!["E"], 1);
+    scheduler.add_task("G", vec!["A"], 1);
+    // A holds G to the diamond, D and E hold the tail
+    assert_eq!(
+        scheduler.articulation_tasks(),
+        vec!["A".to_string(), "D".to_string(), "E".to_string()]
+    );
+}
+
+#[test]
+fn test_articulation_tasks_fork() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 1);
+    scheduler.add_task("B", vec!["A"], 1);
+    scheduler.add_task("C", vec!["A"], 1);
+    // a redundant path from A to C through B
+    scheduler.add_task("D", vec!["B", "C"], 1);
+    scheduler.add_task("E", vec!["A"], 1);
+    assert_eq!(scheduler.articulation_tasks(), vec!["A".to_string()]);
+}
+
+#[test]
+fn test_feedback_task_set() {
+    assert!(crate::example_scheduler().feedback_task_set().is_empty());
+
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec!["C"], 1);
+    scheduler.add_task("B", vec!["A"], 1);
+    scheduler.add_task("C", vec!["B"], 1);
+    scheduler.add_task("D", vec!["C"], 1);
+    scheduler.add_task("E", vec!["E"], 1);
+    let feedback = scheduler.feedback_task_set();
+    assert_eq!(feedback, vec!["A".to_string(), "E".to_string()]);
+
+    for name in &feedback {
+        scheduler.remove_node(name);
+    }
+    assert!(scheduler.topological_order().is_ok());
+}
+
+#[test]
+fn test_transitive_closure() {
+    let scheduler = crate::example_scheduler();
+    let closure = scheduler.transitive_closure().unwrap();
+    let expected: HashSet<String> = ["B", "C", "D"].map(String::from).into();
+    assert_eq!(closure["A"], expected);
+    assert!(closure["D"].is_empty());
+    for name in scheduler.tasks.keys() {
+        assert_eq!(closure[name], scheduler.all_dependents(name).unwrap());
+    }
+}
+
+#[test]
+fn test_would_unblock() {
+    let scheduler = crate::example_scheduler();
+    let mut completed = HashSet::new();
+    assert_eq!(
+        scheduler.would_unblock(&completed, "A"),
+        Ok(vec!["B".to_string(), "C".to_string()])
+    );
+    // D still needs C
+    completed.insert("A".to_string());
+    assert_eq!(scheduler.would_unblock(&completed, "B"), Ok(vec![]));
+    completed.insert("C".to_string());
+    assert_eq!(
+        scheduler.would_unblock(&completed, "B"),
+        Ok(vec!["D".to_string()])
+    );
+    assert_eq!(
+        scheduler.would_unblock(&completed, "X"),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
+
+#[test]
+fn test_deep_chain_traversal() {
+    const LEN: usize = 100_000;
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("t0", vec![], 1);
+    for i in 1..LEN {
+        let prev = format!("t{}", i - 1);
+        scheduler.add_task(&format!("t{i}"), vec![&prev], 1);
+    }
+    let last = format!("t{}", LEN - 1);
+
+    assert_eq!(scheduler.all_dependencies(&last).unwrap().len(), LEN - 1);
+    assert_eq!(scheduler.all_dependents("t0").unwrap().len(), LEN - 1);
+    assert_eq!(scheduler.topological_order().unwrap().len(), LEN);
+
+    scheduler.set_max_traversal_depth(Some(LEN - 1));
+    assert_eq!(scheduler.all_dependencies(&last).unwrap().len(), LEN - 1);
+
+    scheduler.set_max_traversal_depth(Some(10));
+    assert_eq!(
+        scheduler.all_dependencies(&last),
+        Err(ScheduleError::DepthLimitExceeded)
+    );
+    assert_eq!(
+        scheduler.all_dependents("t0"),
+        Err(ScheduleError::DepthLimitExceeded)
+    );
+    // shallow walks are still fine
+    assert_eq!(scheduler.all_dependencies("t10").unwrap().len(), 10);
+}
+
+#[test]
+fn test_unreachable_tasks_ignores_depth_limit() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("a", vec![], 1);
+    scheduler.add_task("b", vec!["a"], 1);
+    scheduler.add_task("c", vec!["b"], 1);
+    scheduler.set_max_traversal_depth(Some(1));
+
+    assert!(scheduler.unreachable_tasks().is_empty());
+    assert_eq!(scheduler.require_all_reachable(), Ok(()));
+    assert_eq!(
+        scheduler.reachable_from(&["a"]),
+        Err(ScheduleError::DepthLimitExceeded)
+    );
+}
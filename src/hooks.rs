@@ -0,0 +1,73 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::TaskScheduler;
+
+/// Why the scheduler's derived results are out of date
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RecomputeReason {
+    TaskAdded,
+    TaskRemoved,
+    DurationChanged,
+    /// Edges (or their lags) were added or removed
+    DependenciesChanged,
+    /// Some other task or scheduler attribute changed, e.g weight, deadline or resources
+    AttributeChanged,
+}
+
+/// Shared so the scheduler stays cheap to clone and `Send`
+#[derive(Clone)]
+pub(crate) struct RecomputeHook(Arc<dyn Fn(RecomputeReason) + Send + Sync>);
+
+impl fmt::Debug for RecomputeHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RecomputeHook")
+    }
+}
+
+impl TaskScheduler {
+    /// Call `hook` every time a mutation invalidates previously computed results.
+    ///
+    /// Nothing is cached at the moment, so every mutation made through the scheduler's
+    /// methods is reported. Edits made directly to the public fields are not seen.
+    /// Replaces any previously set hook
+    pub fn set_recompute_hook<F: Fn(RecomputeReason) + Send + Sync + 'static>(&mut self, hook: F) {
+        self.recompute_hook = Some(RecomputeHook(Arc::new(hook)));
+    }
+
+    pub(crate) fn notify(&mut self, reason: RecomputeReason) {
+        // removing tasks or edges never breaks a topological numbering
+        if reason != RecomputeReason::TaskRemoved {
+            self.topo_order = None;
+        }
+        if let Some(RecomputeHook(hook)) = &self.recompute_hook {
+            hook(reason);
+        }
+    }
+}
+
+#[test]
+fn test_recompute_hook() {
+    use std::sync::Mutex;
+
+    let reasons = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&reasons);
+
+    let mut scheduler = crate::example_scheduler();
+    scheduler.set_recompute_hook(move |reason| log.lock().unwrap().push(reason));
+
+    scheduler.add_task("E", vec!["D"], 1);
+    scheduler.set_duration("E", 5).unwrap();
+    scheduler.set_weight("E", 2).unwrap();
+    // unknown task: nothing changed, nothing reported
+    assert!(scheduler.set_duration("X", 1).is_err());
+
+    assert_eq!(
+        *reasons.lock().unwrap(),
+        vec![
+            RecomputeReason::TaskAdded,
+            RecomputeReason::DurationChanged,
+            RecomputeReason::AttributeChanged,
+        ]
+    );
+}
@@ -0,0 +1,238 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{RecomputeReason, ScheduleError, TaskScheduler};
+
+/// Topological numbering kept up to date edge by edge, see `add_dependency`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TopoOrder {
+    /// Position of every task, dependencies always come before their dependents
+    position: HashMap<String, usize>,
+    /// Task at every position
+    at: Vec<String>,
+}
+
+impl TaskScheduler {
+    /// Add the edge "`task` depends on `dep`", refusing it with `CycleDetected` if it would
+    /// close a cycle. Adding an edge that is already there does nothing.
+    ///
+    /// Meant for editors that add edges one at a time: a topological numbering is kept between
+    /// calls and repaired with the Pearce-Kelly algorithm, which only looks at the tasks
+    /// numbered between `task` and `dep` instead of walking the whole graph.
+    /// Any other mutation drops the numbering (removals keep it, they can't break it)
+    /// and the next call rebuilds it in one pass
+    pub fn add_dependency(&mut self, task: &str, dep: &str) -> Result<(), ScheduleError> {
+        if !self.tasks.contains_key(task) || !self.tasks.contains_key(dep) {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        if self.tasks[task].dependencies.iter().any(|d| d == dep) {
+            return Ok(());
+        }
+        let mut order = match self.topo_order.take() {
+            Some(order) => order,
+            None => self.full_topo_order()?,
+        };
+        let checked = self.reorder(&mut order, task, dep);
+        // a refused edge leaves the graph, and so the numbering, as it was
+        self.topo_order = Some(order);
+        checked?;
+
+        self.tasks
+            .get_mut(task)
+            .expect("checked above")
+            .dependencies
+            .push(dep.to_string());
+        *self.first_level_dep.entry(task.to_string()).or_insert(0) += 1;
+        self.dependents
+            .entry(dep.to_string())
+            .or_default()
+            .push(task.to_string());
+
+        let order = self.topo_order.take();
+        self.record_task(task);
+        self.notify(RecomputeReason::DependenciesChanged);
+        self.topo_order = order;
+        Ok(())
+    }
+
+    /// Number every task with Kahn's algorithm. Dependencies that are not tasks are ignored
+    fn full_topo_order(&self) -> Result<TopoOrder, ScheduleError> {
+        let mut in_degree: HashMap<&str, usize> = self
+            .tasks
+            .values()
+            .map(|task| {
+                let known = task
+                    .dependencies
+                    .iter()
+                    .filter(|dep| self.tasks.contains_key(*dep))
+                    .count();
+                (task.name.as_str(), known)
+            })
+            .collect();
+        let mut roots: Vec<&str> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(&name, _)| name)
+            .collect();
+        roots.sort();
+        let mut queue: VecDeque<&str> = roots.into_iter().collect();
+
+        let mut order = TopoOrder::default();
+        while let Some(name) = queue.pop_front() {
+            order.position.insert(name.to_string(), order.at.len());
+            order.at.push(name.to_string());
+            for dependent in self.dependents.get(name).into_iter().flatten() {
+                if let Some(degree) = in_degree.get_mut(dependent.as_str()) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        queue.push_back(dependent);
+                    }
+                }
+            }
+        }
+        if order.at.len() < self.tasks.len() {
+            return Err(ScheduleError::CycleDetected);
+        }
+        Ok(order)
+    }
+
+    /// Make room in `order` for the edge `dep -> task`, or fail if `task` already reaches `dep`.
+    /// Returns how many tasks had to be looked at
+    fn reorder(
+        &self,
+        order: &mut TopoOrder,
+        task: &str,
+        dep: &str,
+    ) -> Result<usize, ScheduleError> {
+        let lower = order.position[task];
+        let upper = order.position[dep];
+        if upper < lower {
+            return Ok(0);
+        }
+
+        // everything downstream of `task` that is numbered no later than `dep`
+        let mut forward: Vec<&str> = Vec::new();
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut stack: Vec<&str> = vec![task];
+        while let Some(name) = stack.pop() {
+            if !seen.insert(name) {
+                continue;
+            }
+            if name == dep {
+                return Err(ScheduleError::CycleDetected);
+            }
+            forward.push(name);
+            for dependent in self.dependents.get(name).into_iter().flatten() {
+                if order.position.get(dependent).is_some_and(|&at| at <= upper) {
+                    stack.push(dependent);
+                }
+            }
+        }
+
+        // everything upstream of `dep` that is numbered no earlier than `task`
+        let mut backward: Vec<&str> = Vec::new();
+        let mut stack: Vec<&str> = vec![dep];
+        while let Some(name) = stack.pop() {
+            if !seen.insert(name) {
+                continue;
+            }
+            backward.push(name);
+            for upstream in &self.tasks[name].dependencies {
+                if order.position.get(upstream).is_some_and(|&at| at >= lower) {
+                    stack.push(upstream);
+                }
+            }
+        }
+
+        // hand the positions they held back out, upstream part first
+        let by_position = |names: &mut Vec<&str>| names.sort_by_key(|name| order.position[*name]);
+        by_position(&mut forward);
+        by_position(&mut backward);
+        let mut slots: Vec<usize> = forward
+            .iter()
+            .chain(&backward)
+            .map(|name| order.position[*name])
+            .collect();
+        slots.sort();
+
+        let checked = forward.len() + backward.len();
+        for (name, slot) in backward.into_iter().chain(forward).zip(slots) {
+            order.position.insert(name.to_string(), slot);
+            order.at[slot] = name.to_string();
+        }
+        Ok(checked)
+    }
+}
+
+#[test]
+fn test_add_dependency() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.add_dependency("C", "B").unwrap();
+    assert_eq!(scheduler.tasks["C"].dependencies, vec!["A", "B"]);
+    assert_eq!(scheduler.first_level_dep["C"], 2);
+    assert!(scheduler.dependents["B"].contains(&"C".to_string()));
+
+    // A -> B -> C -> D already, so none of these may go backwards
+    assert_eq!(
+        scheduler.add_dependency("A", "D"),
+        Err(ScheduleError::CycleDetected)
+    );
+    assert_eq!(
+        scheduler.add_dependency("B", "C"),
+        Err(ScheduleError::CycleDetected)
+    );
+    assert_eq!(
+        scheduler.add_dependency("A", "A"),
+        Err(ScheduleError::CycleDetected)
+    );
+    assert_eq!(
+        scheduler.add_dependency("A", "X"),
+        Err(ScheduleError::NoTaskFound)
+    );
+    assert_eq!(scheduler.schedule_tasks().unwrap().len(), 4);
+}
+
+#[test]
+fn test_add_dependency_is_local() {
+    // 200 independent chains of 10 tasks
+    let mut scheduler = TaskScheduler::new();
+    for chain in 0..200 {
+        scheduler.add_task(&format!("{chain}.0"), vec![], 1);
+        for link in 1..10 {
+            let dep = format!("{chain}.{}", link - 1);
+            scheduler.add_task(&format!("{chain}.{link}"), vec![&dep], 1);
+        }
+    }
+    let mut order = scheduler.full_topo_order().unwrap();
+    for chain in 0..200 {
+        let first = format!("{chain}.0");
+        let last = format!("{chain}.9");
+        assert_eq!(
+            scheduler.reorder(&mut order, &first, &last),
+            Err(ScheduleError::CycleDetected)
+        );
+    }
+
+    // re-wiring one chain after another only touches the tasks in between
+    for chain in 1..200 {
+        let first = format!("{chain}.0");
+        let before = format!("{}.9", chain - 1);
+        let checked = scheduler.reorder(&mut order, &first, &before).unwrap();
+        assert!(checked <= 20, "looked at {checked} tasks");
+    }
+    for chain in 1..200 {
+        let first = format!("{chain}.0");
+        let before = format!("{}.9", chain - 1);
+        scheduler.add_dependency(&first, &before).unwrap();
+    }
+    let order = scheduler.topo_order.as_ref().unwrap();
+    assert!(scheduler.tasks.values().all(|task| task
+        .dependencies
+        .iter()
+        .all(|dep| order.position[dep] < order.position[&task.name])));
+    // one long chain now, closing it is refused
+    assert_eq!(
+        scheduler.add_dependency("0.0", "199.9"),
+        Err(ScheduleError::CycleDetected)
+    );
+    assert_eq!(scheduler.schedule_tasks().unwrap().len(), 2000);
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use crate::TaskScheduler;
+
+/// First inconsistency `check_integrity` found between `tasks` and the derived maps
+#[derive(Debug, Eq, PartialEq)]
+pub enum IntegrityError {
+    /// `dependent` is in the dependents list of `dependency` more often than it depends on it
+    DuplicateDependent {
+        dependency: String,
+        dependent: String,
+    },
+    /// `dependent` depends on `dependency` but is missing from its dependents list
+    MissingDependent {
+        dependency: String,
+        dependent: String,
+    },
+    /// `dependent` is in the dependents list of `dependency` without depending on it
+    StrayDependent {
+        dependency: String,
+        dependent: String,
+    },
+    /// `first_level_dep` holds `recorded` for `task`, which has `actual` dependencies
+    DegreeMismatch {
+        task: String,
+        recorded: Option<usize>,
+        actual: usize,
+    },
+}
+
+impl TaskScheduler {
+    /// Verify that `first_level_dep` and `dependents` agree with the dependencies in `tasks`.
+    /// A self-test to run after editing the public fields by hand, `rebuild_indices` repairs
+    /// whatever it reports. Tasks are checked in name order
+    pub fn check_integrity(&self) -> Result<(), IntegrityError> {
+        let mut names: Vec<&String> = self.tasks.keys().collect();
+        names.sort();
+        for name in names {
+            let actual = self.tasks[name].dependencies.len();
+            let recorded = self.first_level_dep.get(name).copied();
+            if recorded != Some(actual) {
+                return Err(IntegrityError::DegreeMismatch {
+                    task: name.clone(),
+                    recorded,
+                    actual,
+                });
+            }
+        }
+
+        // (dependency, dependent) => (times declared, times listed)
+        let mut edges: BTreeMap<(&str, &str), (usize, usize)> = BTreeMap::new();
+        for task in self.tasks.values() {
+            for dep in &task.dependencies {
+                edges.entry((dep, &task.name)).or_default().0 += 1;
+            }
+        }
+        for (dep, dependents) in &self.dependents {
+            for dependent in dependents {
+                edges.entry((dep, dependent)).or_default().1 += 1;
+            }
+        }
+        for ((dependency, dependent), (declared, listed)) in edges {
+            let (dependency, dependent) = (dependency.to_string(), dependent.to_string());
+            if declared == 0 {
+                return Err(IntegrityError::StrayDependent {
+                    dependency,
+                    dependent,
+                });
+            }
+            if listed < declared {
+                return Err(IntegrityError::MissingDependent {
+                    dependency,
+                    dependent,
+                });
+            }
+            if listed > declared {
+                return Err(IntegrityError::DuplicateDependent {
+                    dependency,
+                    dependent,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_check_integrity() {
+    let mut scheduler = crate::example_scheduler();
+    assert_eq!(scheduler.check_integrity(), Ok(()));
+
+    scheduler
+        .dependents
+        .get_mut("A")
+        .unwrap()
+        .push("B".to_string());
+    assert_eq!(
+        scheduler.check_integrity(),
+        Err(IntegrityError::DuplicateDependent {
+            dependency: "A".to_string(),
+            dependent: "B".to_string()
+        })
+    );
+
+    scheduler.rebuild_indices();
+    assert_eq!(scheduler.check_integrity(), Ok(()));
+
+    scheduler.tasks.get_mut("C").unwrap().dependencies.clear();
+    assert_eq!(
+        scheduler.check_integrity(),
+        Err(IntegrityError::DegreeMismatch {
+            task: "C".to_string(),
+            recorded: Some(1),
+            actual: 0
+        })
+    );
+    scheduler.first_level_dep.insert("C".to_string(), 0);
+    assert_eq!(
+        scheduler.check_integrity(),
+        Err(IntegrityError::StrayDependent {
+            dependency: "A".to_string(),
+            dependent: "C".to_string()
+        })
+    );
+}
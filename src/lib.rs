@@ -1,18 +1,287 @@
-use std::collections::{HashMap, VecDeque};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+mod alternatives;
+mod binary;
+mod clock;
+mod config;
+mod cpm;
+mod edit;
+mod executor;
+mod export;
+mod format;
+mod fuzz;
+mod graph;
+mod hooks;
+mod incremental;
+mod integrity;
+mod metadata;
+mod metrics;
+mod parallel;
+mod replay;
+mod report;
+mod resources;
+mod rng;
+mod runner;
+mod snapshot;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod visitor;
+
+pub use binary::LoadError;
+pub use clock::{Clock, U32Clock};
+pub use config::{OverflowMode, RoundingMode, ScheduleConfig};
+pub use edit::Collapsed;
+pub use executor::RunReport;
+pub use export::NameMapped;
+pub use format::{CsvFormatter, DotFormatter, MarkdownFormatter, ScheduleFormatter};
+pub use graph::{MAX_ORDERING_STATES, MAX_PATHS};
+pub use hooks::RecomputeReason;
+pub use integrity::IntegrityError;
+pub use parallel::per_worker_timeline;
+pub use replay::Mutation;
+pub use report::AnalysisReport;
+pub use runner::Runner;
+pub use snapshot::SchedulerSnapshot;
+pub use visitor::ScheduleVisitor;
+
+use alternatives::OrGroups;
+use hooks::RecomputeHook;
+use incremental::TopoOrder;
+use visitor::NoopVisitor;
+
+/// Build a `TaskScheduler` from `name => [dependencies] : duration;` entries.
+///
+/// Expands to a sequence of `try_add_task` calls and evaluates to
+/// `Result<TaskScheduler, AddError>`, so a duplicate name is reported instead of panicking.
+///
+/// ```
+/// let scheduler = mo_assignment::tasks! {
+///     "A" => [] : 3;
+///     "B" => ["A"] : 2;
+/// }
+/// .unwrap();
+/// assert_eq!(scheduler.tasks.len(), 2);
+/// ```
+#[macro_export]
+macro_rules! tasks {
+    ($($name:expr => [$($dep:expr),* $(,)?] : $duration:expr);* $(;)?) => {{
+        #[allow(unused_mut)]
+        let mut scheduler = $crate::TaskScheduler::new();
+        #[allow(unused_mut)]
+        let mut result: ::std::result::Result<(), $crate::AddError> = Ok(());
+        $(
+            if result.is_ok() {
+                result = scheduler.try_add_task($name, vec![$($dep),*], $duration);
+            }
+        )*
+        result.map(|()| scheduler)
+    }};
+}
 
 /// A task unit.
 /// A dependencies must be provided by the user.
-#[derive(Debug)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Task {
     pub name: String,
     pub dependencies: Vec<String>,
     pub duration: u32,
+    /// Mandatory wait between a dependency finishing and this task starting.
+    /// Dependencies not listed here have no lag
+    pub lags: HashMap<String, u32>,
+    /// Importance of finishing this task early, used by weighted metrics. Defaults to 1
+    pub weight: u32,
+    /// Time by which this task should be finished, if any
+    pub deadline: Option<u32>,
+    /// Time after which finishing costs `penalty_per_unit` per unit late, see `total_penalty`
+    pub soft_deadline: Option<u32>,
+    /// Cost of every unit of time this task finishes past its `soft_deadline`
+    pub penalty_per_unit: u32,
+    /// Higher priority tasks may preempt lower priority ones in the preemptive scheduler.
+    /// Defaults to 0
+    pub priority: u32,
+    /// Named exclusion groups: the parallel scheduler never runs two tasks sharing a group at once
+    pub exclusion_groups: Vec<String>,
+    /// Amount of each named resource this task holds while it runs
+    pub resources: HashMap<String, u32>,
+    /// Externally pinned start time. The task starts exactly then,
+    /// and scheduling fails if its dependencies can't be done by that time
+    pub fixed_start: Option<u32>,
+    /// Logical group this task is an instance of, see `max_concurrent`
+    pub concurrency_group: Option<String>,
+    /// Most tasks of `concurrency_group` the parallel scheduler runs at once
+    /// when this one is started. Unlike an exclusion group this is a counting limit
+    pub max_concurrent: Option<u32>,
+    /// Overhead before the real work (spin-up). See `schedule_tasks`
+    pub setup: u32,
+    /// Overhead after the real work (cleanup). See `schedule_tasks`
+    pub teardown: u32,
+    /// Free-form `key => value` tags, e.g. the owning team
+    pub metadata: HashMap<String, String>,
+    /// Protected tasks are only removed by `remove_task_forced`
+    pub protected: bool,
+    /// Divisible work can be spread over several workers, see `schedule_parallel_divisible`
+    pub divisible: bool,
+    /// Alternatives: besides all `dependencies`, the task waits for at least one task
+    /// out of each group, see `add_task_with_or_deps`
+    pub or_groups: Vec<Vec<String>>,
+    /// Tasks sharing an affinity run back to back on one worker when possible,
+    /// see `schedule_work_stealing_affinity`
+    pub affinity: Option<String>,
+    /// What `collapse` replaced with this task, so `expand` can restore it
+    pub collapsed: Option<Collapsed>,
+    /// Tasks `run_parallel` only runs if this one succeeds, see `set_branches`
+    pub on_success: Vec<String>,
+    /// Tasks `run_parallel` only runs if this one fails, see `set_branches`
+    pub on_failure: Vec<String>,
+}
+
+impl Default for Task {
+    fn default() -> Self {
+        Task {
+            name: String::new(),
+            dependencies: Vec::new(),
+            duration: 0,
+            lags: HashMap::new(),
+            weight: 1,
+            deadline: None,
+            soft_deadline: None,
+            penalty_per_unit: 0,
+            priority: 0,
+            exclusion_groups: Vec::new(),
+            resources: HashMap::new(),
+            fixed_start: None,
+            concurrency_group: None,
+            max_concurrent: None,
+            setup: 0,
+            teardown: 0,
+            metadata: HashMap::new(),
+            protected: false,
+            divisible: false,
+            or_groups: Vec::new(),
+            affinity: None,
+            collapsed: None,
+            on_success: Vec::new(),
+            on_failure: Vec::new(),
+        }
+    }
+}
+
+impl Task {
+    /// Lag this task must wait after `dep` finishes
+    pub fn lag(&self, dep: &str) -> u32 {
+        self.lags.get(dep).copied().unwrap_or(0)
+    }
+}
+
+/// A task placed on the timeline
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ScheduledTask {
+    pub name: String,
+    pub start: u32,
+    pub duration: u32,
+}
+
+impl From<(String, u32, u32)> for ScheduledTask {
+    fn from((name, start, duration): (String, u32, u32)) -> Self {
+        ScheduledTask {
+            name,
+            start,
+            duration,
+        }
+    }
+}
+
+impl ScheduledTask {
+    /// When the task is done: `start + duration`, saturating at `u32::MAX`.
+    /// Schedules built by the scheduler never go past that, only hand-made entries can
+    pub fn finish(&self) -> u32 {
+        self.start.saturating_add(self.duration)
+    }
+}
+
+/// A placed task with how long it sat ready before starting, see `schedule_detailed`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DetailedTask {
+    pub name: String,
+    pub start: u32,
+    pub duration: u32,
+    pub wait: u32,
+}
+
+/// A schedule with lookup by task name. Derefs to the entries in their original order
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Schedule {
+    entries: Vec<ScheduledTask>,
+    index: HashMap<String, usize>,
+}
+
+impl Schedule {
+    /// Entry of task `name`, if it is part of the schedule
+    pub fn get(&self, name: &str) -> Option<&ScheduledTask> {
+        self.index.get(name).map(|&i| &self.entries[i])
+    }
+
+    /// The entries, in their original order
+    pub fn into_vec(self) -> Vec<ScheduledTask> {
+        self.entries
+    }
+}
+
+impl From<Vec<ScheduledTask>> for Schedule {
+    fn from(entries: Vec<ScheduledTask>) -> Self {
+        let index = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.name.clone(), i))
+            .collect();
+        Schedule { entries, index }
+    }
+}
+
+impl std::ops::Deref for Schedule {
+    type Target = [ScheduledTask];
+
+    fn deref(&self) -> &[ScheduledTask] {
+        &self.entries
+    }
+}
+
+/// Order a schedule by start time, breaking ties by name
+pub(crate) fn sort_by_start(schedule: &mut [ScheduledTask]) {
+    schedule.sort_by(|a, b| (a.start, &a.name).cmp(&(b.start, &b.name)));
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum ScheduleError {
     NoTaskFound,
     CycleDetected,
+    /// A pre-schedule guard vetoed the graph with the given reason
+    GuardRejected(String),
+    /// The graph has more tasks than the requested analysis supports
+    GraphTooLarge,
+    /// The named task needs more of a resource than its total capacity, so it can never run
+    ResourceUnavailable(String),
+    /// The named task can't honor its pinned start time
+    ConstraintViolation(String),
+    /// The schedule runs past `u32::MAX`
+    DurationOverflow,
+    /// The named task is protected and can't be removed without forcing it
+    Protected(String),
+    /// A task with this name already exists
+    DuplicateTask(String),
+    /// The named task is not reachable from any root, see `require_all_reachable`
+    OrphanTask(String),
+    /// A transitive walk went further than `set_max_traversal_depth` allows
+    DepthLimitExceeded,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum AddError {
+    /// A task with this name was already added
+    DuplicateTask(String),
+    /// A dependency that has not been added yet
+    MissingDependency(String),
 }
 
 /// Object that act as scheduler
@@ -28,6 +297,46 @@ pub struct TaskScheduler {
     /// see what other task depends on it.
     /// akin to reverse-dependency
     pub dependents: HashMap<String, Vec<String>>,
+    /// How much of each named resource is available at any moment.
+    /// Resources without a capacity here are unlimited
+    pub resource_capacities: HashMap<String, u32>,
+    /// Called on every mutation, see `set_recompute_hook`
+    recompute_hook: Option<RecomputeHook>,
+    /// Log of mutations once `enable_recording` was called
+    recording: Option<Vec<Mutation>>,
+    /// Kept between `add_dependency` calls, dropped by any other mutation
+    topo_order: Option<TopoOrder>,
+    /// How many edges a transitive walk may follow, see `set_max_traversal_depth`
+    max_traversal_depth: Option<usize>,
+}
+
+impl Default for TaskScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Two schedulers are equal when they hold the same tasks and the same edges.
+/// Hooks and recordings are not compared.
+/// The order within a `dependents` list only reflects insertion order, so it is not compared
+impl PartialEq for TaskScheduler {
+    fn eq(&self, other: &Self) -> bool {
+        let sorted = |dependents: &HashMap<String, Vec<String>>| -> HashMap<String, Vec<String>> {
+            dependents
+                .iter()
+                .filter(|(_, list)| !list.is_empty())
+                .map(|(name, list)| {
+                    let mut list = list.clone();
+                    list.sort();
+                    (name.clone(), list)
+                })
+                .collect()
+        };
+        self.tasks == other.tasks
+            && self.first_level_dep == other.first_level_dep
+            && sorted(&self.dependents) == sorted(&other.dependents)
+            && self.resource_capacities == other.resource_capacities
+    }
 }
 
 impl TaskScheduler {
@@ -37,14 +346,34 @@ impl TaskScheduler {
             tasks: HashMap::new(),
             first_level_dep: HashMap::new(),
             dependents: HashMap::new(),
+            resource_capacities: HashMap::new(),
+            recompute_hook: None,
+            recording: None,
+            topo_order: None,
+            max_traversal_depth: None,
         }
     }
 
     /// Add task to the scheduler
+    ///
+    /// # Panics
+    /// If a task with the same name already exists, see `try_add_task` for the fallible version
     pub fn add_task(&mut self, name: &str, dependencies: Vec<&str>, duration: u32) {
+        if self.try_add_task(name, dependencies, duration).is_err() {
+            panic!("Task with the same name already exists");
+        }
+    }
+
+    /// Add task to the scheduler, returning an error instead of panicking on duplicates
+    pub fn try_add_task(
+        &mut self,
+        name: &str,
+        dependencies: Vec<&str>,
+        duration: u32,
+    ) -> Result<(), AddError> {
         // Check if task with same name exists already
         if self.tasks.contains_key(name) {
-            panic!("Task with the same name already exists");
+            return Err(AddError::DuplicateTask(name.to_string()));
         }
 
         // create a task object
@@ -52,11 +381,355 @@ impl TaskScheduler {
             name: name.to_string(),
             dependencies: dependencies.iter().map(|&d| d.to_string()).collect(),
             duration,
+            ..Default::default()
         };
 
+        self.insert_task(task);
+        Ok(())
+    }
+
+    /// Add task whose dependencies each carry a lag,
+    /// so the task starts no earlier than `dependency finish + lag` for every dependency
+    pub fn add_task_with_lags(
+        &mut self,
+        name: &str,
+        dependencies: Vec<(&str, u32)>,
+        duration: u32,
+    ) -> Result<(), AddError> {
+        if self.tasks.contains_key(name) {
+            return Err(AddError::DuplicateTask(name.to_string()));
+        }
+        self.insert_task(Task {
+            name: name.to_string(),
+            dependencies: dependencies
+                .iter()
+                .map(|&(dep, _)| dep.to_string())
+                .collect(),
+            duration,
+            lags: dependencies
+                .into_iter()
+                .filter(|&(_, lag)| lag > 0)
+                .map(|(dep, lag)| (dep.to_string(), lag))
+                .collect(),
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    /// Add a task that repeats every `period` time units, as `count` instances
+    /// `name#0`, `name#1` and so on. The first instance waits for `dependencies`, every later
+    /// one for the instance before it, with a lag that puts their starts `period` apart.
+    /// A period shorter than `duration` runs the instances back to back.
+    ///
+    /// Nothing is added if any of the instance names is already taken
+    pub fn add_recurring(
+        &mut self,
+        name: &str,
+        dependencies: Vec<&str>,
+        duration: u32,
+        period: u32,
+        count: u32,
+    ) -> Result<(), AddError> {
+        let instances: Vec<String> = (0..count).map(|i| format!("{name}#{i}")).collect();
+        if let Some(taken) = instances.iter().find(|i| self.tasks.contains_key(*i)) {
+            return Err(AddError::DuplicateTask(taken.clone()));
+        }
+
+        let lag = period.saturating_sub(duration);
+        for (i, instance) in instances.iter().enumerate() {
+            match i.checked_sub(1) {
+                None => self.try_add_task(instance, dependencies.clone(), duration)?,
+                Some(prev) => {
+                    self.add_task_with_lags(instance, vec![(&instances[prev], lag)], duration)?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Change the duration of an existing task
+    pub fn set_duration(&mut self, name: &str, duration: u32) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.duration = duration;
+        self.record(|| Mutation::SetDuration {
+            name: name.to_string(),
+            duration,
+        });
+        self.notify(RecomputeReason::DurationChanged);
+        Ok(())
+    }
+
+    /// Change several durations at once. All names are checked first,
+    /// so on `NoTaskFound` none of the durations has changed.
+    /// The whole batch is recorded and reported to the recompute hook once
+    pub fn set_durations(&mut self, updates: &[(&str, u32)]) -> Result<(), ScheduleError> {
+        if updates
+            .iter()
+            .any(|(name, _)| !self.tasks.contains_key(*name))
+        {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        for &(name, duration) in updates {
+            self.tasks.get_mut(name).expect("checked above").duration = duration;
+        }
+        self.record(|| {
+            Mutation::SetDurations(
+                updates
+                    .iter()
+                    .map(|&(name, duration)| (name.to_string(), duration))
+                    .collect(),
+            )
+        });
+        self.notify(RecomputeReason::DurationChanged);
+        Ok(())
+    }
+
+    /// Set the weight of an existing task
+    pub fn set_weight(&mut self, name: &str, weight: u32) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.weight = weight;
+        self.record_task(name);
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Set or clear the deadline of an existing task
+    pub fn set_deadline(&mut self, name: &str, deadline: Option<u32>) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.deadline = deadline;
+        self.record_task(name);
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Set the priority of an existing task
+    pub fn set_priority(&mut self, name: &str, priority: u32) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.priority = priority;
+        self.record_task(name);
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Set the setup and teardown overhead of an existing task
+    pub fn set_overhead(
+        &mut self,
+        name: &str,
+        setup: u32,
+        teardown: u32,
+    ) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.setup = setup;
+        task.teardown = teardown;
+        self.record_task(name);
+        self.notify(RecomputeReason::DurationChanged);
+        Ok(())
+    }
+
+    /// Mark (or unmark) an existing task as divisible across workers
+    pub fn set_divisible(&mut self, name: &str, divisible: bool) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.divisible = divisible;
+        self.record_task(name);
+        self.notify(RecomputeReason::DurationChanged);
+        Ok(())
+    }
+
+    /// Set or clear the soft deadline of an existing task, with what each unit late costs
+    pub fn set_soft_deadline(
+        &mut self,
+        name: &str,
+        soft_deadline: Option<u32>,
+        penalty_per_unit: u32,
+    ) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.soft_deadline = soft_deadline;
+        task.penalty_per_unit = penalty_per_unit;
+        self.record_task(name);
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Set or clear the affinity group of an existing task
+    pub fn set_affinity(
+        &mut self,
+        name: &str,
+        affinity: Option<&str>,
+    ) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.affinity = affinity.map(str::to_string);
+        self.record_task(name);
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Set the conditional branches of an existing task: when `run_parallel` runs it, the
+    /// `on_success` tasks only run if it succeeds, the `on_failure` ones only if it fails, and
+    /// the other branch is skipped. A branch task also waits for its own dependencies.
+    ///
+    /// Only the executor follows these edges, the schedulers and analyses don't see them.
+    /// Every named branch task must exist, and branches can't loop back to the task, through
+    /// other branches or dependencies: that is `CycleDetected` and nothing changes
+    pub fn set_branches(
+        &mut self,
+        name: &str,
+        on_success: Vec<&str>,
+        on_failure: Vec<&str>,
+    ) -> Result<(), ScheduleError> {
+        if !self.tasks.contains_key(name)
+            || on_success
+                .iter()
+                .chain(&on_failure)
+                .any(|branch| !self.tasks.contains_key(*branch))
+        {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        let task = self.tasks.get_mut(name).expect("checked above");
+        let old_success = std::mem::replace(
+            &mut task.on_success,
+            on_success.into_iter().map(str::to_string).collect(),
+        );
+        let old_failure = std::mem::replace(
+            &mut task.on_failure,
+            on_failure.into_iter().map(str::to_string).collect(),
+        );
+        if self.has_branch_cycle() {
+            let task = self.tasks.get_mut(name).expect("checked above");
+            task.on_success = old_success;
+            task.on_failure = old_failure;
+            return Err(ScheduleError::CycleDetected);
+        }
+        self.record_task(name);
+        self.notify(RecomputeReason::DependenciesChanged);
+        Ok(())
+    }
+
+    /// Whether following dependencies and branches together can lead back to where it started
+    fn has_branch_cycle(&self) -> bool {
+        let mut in_degree: HashMap<&str, usize> =
+            self.tasks.keys().map(|name| (name.as_str(), 0)).collect();
+        let mut edges: HashMap<&str, Vec<&str>> = HashMap::new();
+        for task in self.tasks.values() {
+            let before = task.dependencies.iter().map(|dep| (dep, &task.name));
+            let after = task
+                .on_success
+                .iter()
+                .chain(&task.on_failure)
+                .map(|branch| (&task.name, branch));
+            for (from, to) in before.chain(after) {
+                if self.tasks.contains_key(from) && self.tasks.contains_key(to) {
+                    edges.entry(from).or_default().push(to);
+                    *in_degree.get_mut(to.as_str()).expect("checked above") += 1;
+                }
+            }
+        }
+
+        let mut ready: Vec<&str> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(&name, _)| name)
+            .collect();
+        let mut visited = 0;
+        while let Some(name) = ready.pop() {
+            visited += 1;
+            for &next in edges.get(name).into_iter().flatten() {
+                let degree = in_degree.get_mut(next).expect("every task has a degree");
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+        visited < self.tasks.len()
+    }
+
+    /// Pin (or unpin) the start time of an existing task
+    pub fn set_fixed_start(&mut self, name: &str, start: Option<u32>) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.fixed_start = start;
+        self.record_task(name);
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Put `tasks` in the exclusion group `group`, like a named lock they all have to hold.
+    /// Fails without changing anything if any of the tasks is unknown
+    pub fn add_exclusion_group(
+        &mut self,
+        group: &str,
+        tasks: &[&str],
+    ) -> Result<(), ScheduleError> {
+        if tasks.iter().any(|name| !self.tasks.contains_key(*name)) {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        for name in tasks {
+            let groups = &mut self
+                .tasks
+                .get_mut(*name)
+                .expect("checked above")
+                .exclusion_groups;
+            if !groups.iter().any(|g| g == group) {
+                groups.push(group.to_string());
+            }
+            self.record_task(name);
+        }
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Make `tasks` instances of the logical group `group`,
+    /// of which at most `max_concurrent` run at the same time.
+    /// Fails without changing anything if any of the tasks is unknown
+    pub fn add_concurrency_group(
+        &mut self,
+        group: &str,
+        max_concurrent: u32,
+        tasks: &[&str],
+    ) -> Result<(), ScheduleError> {
+        if tasks.iter().any(|name| !self.tasks.contains_key(*name)) {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        for name in tasks {
+            let task = self.tasks.get_mut(*name).expect("checked above");
+            task.concurrency_group = Some(group.to_string());
+            task.max_concurrent = Some(max_concurrent);
+            self.record_task(name);
+        }
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Like `try_add_task`, but every dependency must already be in the scheduler.
+    ///
+    /// This forbids forward references, so graphs have to be built bottom-up.
+    /// It is stricter than the default lazy model, where a missing dependency only
+    /// shows up once scheduling is attempted
+    pub fn add_task_strict(
+        &mut self,
+        name: &str,
+        dependencies: Vec<&str>,
+        duration: u32,
+    ) -> Result<(), AddError> {
+        if self.tasks.contains_key(name) {
+            return Err(AddError::DuplicateTask(name.to_string()));
+        }
+        if let Some(missing) = dependencies
+            .iter()
+            .find(|dep| !self.tasks.contains_key(**dep))
+        {
+            return Err(AddError::MissingDependency(missing.to_string()));
+        }
+        self.try_add_task(name, dependencies, duration)
+    }
+
+    /// Store an already built task and index its dependencies.
+    /// Caller is responsible for checking the name is not taken
+    pub(crate) fn insert_task(&mut self, task: Task) {
+        let name = task.name.clone();
+
         // store it's dependencies count
         self.first_level_dep
-            .insert(name.to_string(), dependencies.len());
+            .insert(name.clone(), task.dependencies.len());
 
         // a lookup table to get this task's dependents
         // all of it's dependencies will have this task as their dependents
@@ -64,18 +737,565 @@ impl TaskScheduler {
             self.dependents
                 .entry(dep.to_string())
                 .or_default()
-                .push(name.to_string());
+                .push(name.clone());
         }
 
-        self.tasks.insert(name.to_string(), task);
+        self.record(|| Mutation::AddTask(task.clone()));
+        self.tasks.insert(name, task);
+        self.notify(RecomputeReason::TaskAdded);
     }
 
+    /// Recompute `first_level_dep` and `dependents` from `tasks` alone.
+    /// Needed after editing the public `tasks` field directly, which bypasses the bookkeeping.
+    /// Dependents lists are filled in name order
+    pub fn rebuild_indices(&mut self) {
+        let mut names: Vec<&String> = self.tasks.keys().collect();
+        names.sort();
+
+        self.first_level_dep.clear();
+        self.dependents.clear();
+        for name in names {
+            let task = &self.tasks[name];
+            self.first_level_dep
+                .insert(name.clone(), task.dependencies.len());
+            for dep in &task.dependencies {
+                self.dependents
+                    .entry(dep.clone())
+                    .or_default()
+                    .push(name.clone());
+            }
+        }
+        self.notify(RecomputeReason::DependenciesChanged);
+    }
+
+    /// Add tasks one by one from `iter`, stopping at the first failure.
+    ///
+    /// This is **not** atomic: tasks that were added before the failing one stay in the
+    /// scheduler, since rolling back an arbitrary stream is impractical.
+    /// On success returns how many tasks were added
+    pub fn extend_fallible<I: IntoIterator<Item = (String, Vec<String>, u32)>>(
+        &mut self,
+        iter: I,
+    ) -> Result<usize, AddError> {
+        let mut added = 0;
+        for (name, dependencies, duration) in iter {
+            let dependencies = dependencies.iter().map(String::as_str).collect();
+            self.try_add_task(&name, dependencies, duration)?;
+            added += 1;
+        }
+        Ok(added)
+    }
+
+    /// Consume the scheduler, handing back the owned tasks
+    pub fn into_tasks(self) -> HashMap<String, Task> {
+        self.tasks
+    }
+
+    /// Consume the scheduler, handing back the owned tasks sorted by name
+    pub fn into_sorted_vec(self) -> Vec<Task> {
+        let mut tasks: Vec<Task> = self.tasks.into_values().collect();
+        tasks.sort_by(|a, b| a.name.cmp(&b.name));
+        tasks
+    }
+
+    /// Run `guard` against the whole graph before scheduling.
+    /// If the guard returns an error, scheduling is skipped and the message
+    /// is surfaced as `ScheduleError::GuardRejected`
+    pub fn schedule_with_guard<F: Fn(&TaskScheduler) -> Result<(), String>>(
+        &self,
+        guard: F,
+    ) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
+        guard(self).map_err(ScheduleError::GuardRejected)?;
+        self.schedule_tasks()
+    }
+
+    /// Same as `schedule_tasks`, as structured entries
+    pub fn schedule(&self) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        Ok(self
+            .schedule_tasks()?
+            .into_iter()
+            .map(ScheduledTask::from)
+            .collect())
+    }
+
+    /// `schedule`, handing out one entry at a time, so a huge schedule can be written out
+    /// as it is computed.
+    ///
+    /// Anything that would make `schedule` fail (a cycle, a missing dependency, a pinned
+    /// start that can't be honored, an overflow) is reported here, before the first entry:
+    /// the tasks are placed once without keeping them to check that, then again lazily.
+    /// Peak memory is the bookkeeping of one run, the ready degree and finish time of every
+    /// task, never the entries themselves
+    pub fn schedule_iter(&self) -> Result<impl Iterator<Item = ScheduledTask> + '_, ScheduleError> {
+        for placed in SingleRun::new(self, &U32Clock, |_| 0, NoopVisitor, &[]) {
+            placed?;
+        }
+        Ok(SingleRun::new(self, &U32Clock, |_| 0, NoopVisitor, &[])
+            .map(|placed| ScheduledTask::from(placed.expect("validated above"))))
+    }
+
+    /// `schedule`, with every task's `wait`: the time from it being ready (all dependencies done
+    /// and their lags over, 0 for roots) to its setup beginning.
+    /// On the single machine that is time spent behind unrelated tasks, or idling towards a
+    /// pinned start or past a blackout. Waiting on dependencies themselves does not count
+    pub fn schedule_detailed(&self) -> Result<Vec<DetailedTask>, ScheduleError> {
+        let schedule = self.schedule_tasks()?;
+        // when each task frees its dependents, teardown included
+        let released: HashMap<&String, u32> = schedule
+            .iter()
+            .map(|(name, start, duration)| (name, start + duration + self.tasks[name].teardown))
+            .collect();
+        Ok(schedule
+            .iter()
+            .map(|(name, start, duration)| {
+                let task = &self.tasks[name];
+                let ready = task
+                    .dependencies
+                    .iter()
+                    .filter_map(|dep| Some(released.get(dep)? + task.lag(dep)))
+                    .max()
+                    .unwrap_or(0);
+                DetailedTask {
+                    name: name.clone(),
+                    start: *start,
+                    duration: *duration,
+                    wait: (start - task.setup).saturating_sub(ready),
+                }
+            })
+            .collect())
+    }
+
+    /// Same as `schedule_tasks`, with start and duration as `f64` for tooling that expects
+    /// fractional times
+    pub fn schedule_f64(&self) -> Result<Vec<(String, f64, f64)>, ScheduleError> {
+        Ok(self
+            .schedule_tasks()?
+            .into_iter()
+            .map(|(name, start, duration)| (name, start as f64, duration as f64))
+            .collect())
+    }
+
+    /// Same as `schedule`, sorted by start time and then by name
+    pub fn schedule_sorted_by_start(&self) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let mut schedule = self.schedule()?;
+        sort_by_start(&mut schedule);
+        Ok(schedule)
+    }
+
+    /// Single machine schedule as `(name, start, duration)` in execution order.
+    ///
+    /// A task with setup and teardown holds the machine for `setup + duration + teardown`.
+    /// The reported start and finish (`start + duration`) are those of the productive work,
+    /// while dependents and the next task wait until teardown is over.
+    /// A pinned start time refers to the beginning of setup
     pub fn schedule_tasks(&self) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
-        let mut zero_in_degree: VecDeque<String> = VecDeque::new();
-        let mut in_degree = self.first_level_dep.clone();
-        let mut order: Vec<(String, u32, u32)> = Vec::new();
+        // plain first-come first-served among ready tasks
+        self.schedule_tasks_with_clock(&U32Clock)
+    }
+
+    /// Start time of every task for throughput on very large graphs.
+    ///
+    /// A single Kahn pass that only looks at durations, dependencies and OR-groups:
+    /// lags, pinned start times and setup/teardown are ignored, and no visitor is called.
+    /// It skips building the ordered `(name, start, duration)` list and the per-task finish
+    /// times `schedule_tasks` keeps, so it does a fraction of the allocations.
+    /// For graphs without those features the starts are the same as `schedule_tasks`
+    pub fn schedule_fast(&self) -> Result<HashMap<String, u32>, ScheduleError> {
+        let mut in_degree = self.ready_degrees();
+        let mut or_groups = OrGroups::new(&self.tasks);
+        let mut ready: VecDeque<&str> = self
+            .tasks
+            .keys()
+            .filter(|name| in_degree.get(*name) == Some(&0))
+            .map(String::as_str)
+            .collect();
+        let mut starts: HashMap<String, u32> = HashMap::with_capacity(self.tasks.len());
         let mut time: u32 = 0;
 
+        while let Some(name) = ready.pop_front() {
+            let Some(task) = self.tasks.get(name) else {
+                continue;
+            };
+            starts.insert(name.to_string(), time);
+            time = time
+                .checked_add(task.duration)
+                .ok_or(ScheduleError::DurationOverflow)?;
+            let dependents = self.dependents.get(name).into_iter().flatten();
+            for neighbor in dependents
+                .map(String::as_str)
+                .chain(or_groups.finished(name))
+            {
+                if let Some(degree) = in_degree.get_mut(neighbor) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        ready.push_back(neighbor);
+                    }
+                }
+            }
+        }
+
+        if starts.len() < self.tasks.len() {
+            return Err(ScheduleError::CycleDetected);
+        }
+        Ok(starts)
+    }
+
+    /// Like `schedule_tasks`, but whenever several tasks are ready
+    /// the smallest one according to `compare` runs first.
+    /// Ties are broken by name so the result is deterministic
+    pub fn schedule_tasks_by<F: Fn(&Task, &Task) -> Ordering>(
+        &self,
+        compare: F,
+    ) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
+        self.schedule_with_picker(
+            |ready| {
+                let mut best = 0;
+                for (index, name) in ready.iter().enumerate().skip(1) {
+                    let (current, candidate) = (&self.tasks[&ready[best]], &self.tasks[name]);
+                    if compare(candidate, current).then_with(|| name.cmp(&ready[best]))
+                        == Ordering::Less
+                    {
+                        best = index;
+                    }
+                }
+                best
+            },
+            &mut NoopVisitor,
+        )
+    }
+
+    /// Earliest deadline first: among ready tasks, the one with the nearest deadline runs first.
+    /// Tasks without a deadline go after every task that has one
+    pub fn schedule_edf(&self) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let schedule = self.schedule_tasks_by(|a, b| match (a.deadline, b.deadline) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })?;
+        Ok(schedule.into_iter().map(ScheduledTask::from).collect())
+    }
+
+    /// Lawler's algorithm: the order minimizing the maximum lateness (`finish - deadline`)
+    /// on the single machine under the dependencies. The order is built from the back: out of
+    /// the tasks nothing unplaced depends on, the one with the latest deadline goes last.
+    /// Tasks without a deadline count as due last.
+    ///
+    /// Optimal as long as the machine never idles; lags and pinned starts are still honored,
+    /// but can make it miss the optimum
+    pub fn schedule_min_max_lateness(&self) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let mut waiting_on: HashMap<&str, usize> =
+            self.tasks.keys().map(|name| (name.as_str(), 0)).collect();
+        for task in self.tasks.values() {
+            for dep in &task.dependencies {
+                if let Some(count) = waiting_on.get_mut(dep.as_str()) {
+                    *count += 1;
+                }
+            }
+        }
+        let due = |name: &str| self.tasks[name].deadline.unwrap_or(u32::MAX);
+        let mut last: BinaryHeap<(u32, &str)> = waiting_on
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&name, _)| (due(name), name))
+            .collect();
+
+        // position of every task, counted from the end
+        let mut from_end: HashMap<&str, usize> = HashMap::new();
+        while let Some((_, name)) = last.pop() {
+            from_end.insert(name, from_end.len());
+            for dep in &self.tasks[name].dependencies {
+                if let Some(count) = waiting_on.get_mut(dep.as_str()) {
+                    *count -= 1;
+                    if *count == 0 {
+                        last.push((due(dep), dep.as_str()));
+                    }
+                }
+            }
+        }
+        if from_end.len() < self.tasks.len() {
+            return Err(ScheduleError::CycleDetected);
+        }
+
+        // the order is a topological one, so the next task in it is always ready
+        let schedule = self.schedule_with_picker(
+            |ready| {
+                (0..ready.len())
+                    .max_by_key(|&index| from_end[ready[index].as_str()])
+                    .expect("pick is only called with ready tasks")
+            },
+            &mut NoopVisitor,
+        )?;
+        Ok(schedule.into_iter().map(ScheduledTask::from).collect())
+    }
+
+    /// Smith's rule within the precedence constraints: among ready tasks, the one with the
+    /// highest `weight / duration` runs first. Optimal for the weighted sum of completion times
+    /// without dependencies, a good heuristic with them. Zero-duration tasks go first
+    pub fn schedule_min_weighted_completion(&self) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let schedule = self.schedule_tasks_by(|a, b| {
+            // a / da > b / db, compared without dividing
+            let (a_weight, a_duration) = (a.weight as u64, a.duration as u64);
+            let (b_weight, b_duration) = (b.weight as u64, b.duration as u64);
+            (b_weight * a_duration).cmp(&(a_weight * b_duration))
+        })?;
+        Ok(schedule.into_iter().map(ScheduledTask::from).collect())
+    }
+
+    /// Single-threaded schedule where the next task is drawn at random among the ready ones.
+    /// The same seed always gives the same schedule, different seeds explore different
+    /// valid orderings
+    pub fn schedule_seeded(&self, seed: u64) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let mut rng = rng::SplitMix64::new(seed);
+        let schedule = self.schedule_with_picker(
+            |ready| {
+                // draw from the ready set sorted by name, so that only the seed decides
+                let mut by_name: Vec<usize> = (0..ready.len()).collect();
+                by_name.sort_by(|&a, &b| ready[a].cmp(&ready[b]));
+                by_name[rng.below(ready.len())]
+            },
+            &mut NoopVisitor,
+        )?;
+        Ok(schedule.into_iter().map(ScheduledTask::from).collect())
+    }
+
+    /// Whether the single-threaded start times are independent of how ties among ready tasks
+    /// are broken. Tries name order, reverse name order and a handful of seeded random orders,
+    /// so `true` is strong evidence rather than proof; `false` is always backed by two
+    /// differing schedules
+    pub fn is_schedule_deterministic(&self) -> Result<bool, ScheduleError> {
+        let starts = |schedule: Vec<ScheduledTask>| -> HashMap<String, u32> {
+            schedule.into_iter().map(|t| (t.name, t.start)).collect()
+        };
+        let by_name = starts(
+            self.schedule_tasks_by(|_, _| Ordering::Equal)?
+                .into_iter()
+                .map(ScheduledTask::from)
+                .collect(),
+        );
+        let reversed = starts(
+            self.schedule_tasks_by(|a, b| b.name.cmp(&a.name))?
+                .into_iter()
+                .map(ScheduledTask::from)
+                .collect(),
+        );
+        if by_name != reversed {
+            return Ok(false);
+        }
+        for seed in 0..8 {
+            if starts(self.schedule_seeded(seed)?) != by_name {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// What-if schedule with every duration multiplied by `factor`, leaving the stored ones alone.
+    /// Scaled durations are rounded half to even, so rounding does not drift in one direction.
+    /// Negative factors are treated as 0, a scaled duration past `u32::MAX` is a
+    /// `DurationOverflow`
+    pub fn schedule_with_factor(&self, factor: f64) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        self.schedule_with_factor_rounded(factor, RoundingMode::default())
+    }
+
+    /// `schedule_with_factor`, rounding the scaled durations with `rounding`
+    pub fn schedule_with_factor_rounded(
+        &self,
+        factor: f64,
+        rounding: RoundingMode,
+    ) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let mut scaled = self.detached_copy();
+        for task in scaled.tasks.values_mut() {
+            let duration = rounding.round(task.duration as f64 * factor);
+            if duration > u32::MAX as f64 {
+                return Err(ScheduleError::DurationOverflow);
+            }
+            task.duration = duration as u32;
+        }
+        scaled.schedule()
+    }
+
+    /// `schedule_tasks` with the knobs in `config`
+    pub fn schedule_with_config(
+        &self,
+        config: &ScheduleConfig,
+    ) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
+        self.schedule_single(&config.overflow, |_| 0, &mut NoopVisitor, &[])
+    }
+
+    /// Single-threaded schedule where nothing runs during the `[start, end)` `blackouts`.
+    /// A task that would overlap one waits until it is over, it is never split around it.
+    /// A pinned task overlapping a blackout is a `ConstraintViolation`
+    pub fn schedule_with_blackouts(
+        &self,
+        blackouts: &[(u32, u32)],
+    ) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let schedule = self.schedule_single(&U32Clock, |_| 0, &mut NoopVisitor, blackouts)?;
+        Ok(schedule.into_iter().map(ScheduledTask::from).collect())
+    }
+
+    /// Single-threaded schedule where each task takes the machine at least `gap` after the
+    /// previous one did, even if the machine is free sooner, to space out the starts.
+    /// That is the start of its setup if it has one. A pinned task that ends up pushed
+    /// past its time is a `ConstraintViolation`
+    pub fn schedule_with_min_gap(&self, gap: u32) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        SingleRun::new(self, &U32Clock, |_| 0, NoopVisitor, &[])
+            .with_min_gap(gap)
+            .map(|placed| placed.map(ScheduledTask::from))
+            .collect()
+    }
+
+    /// Makespan of the single-threaded schedule when the tasks in `releases` can't take the
+    /// machine before their release time (nor before their dependencies are done, as usual).
+    /// Unknown names are `NoTaskFound`
+    pub fn completion_time_with_releases(
+        &self,
+        releases: &HashMap<String, u32>,
+    ) -> Result<u32, ScheduleError> {
+        if releases.keys().any(|name| !self.tasks.contains_key(name)) {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        let schedule = SingleRun::new(self, &U32Clock, |_| 0, NoopVisitor, &[])
+            .with_releases(releases)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(parallel::makespan_of(&schedule))
+    }
+
+    /// Incremental rebuild, like make: schedule only the `dirty` tasks and everything that
+    /// transitively depends on them. Clean tasks are treated as already done,
+    /// so stale tasks don't wait for them. Unknown names are `NoTaskFound`
+    pub fn schedule_dirty(
+        &self,
+        dirty: &HashSet<String>,
+    ) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let dirty: Vec<&str> = dirty.iter().map(String::as_str).collect();
+        let stale = self.reachable_from(&dirty)?;
+
+        let mut rebuild = self.detached_copy();
+        for name in self.tasks.keys().filter(|name| !stale.contains(*name)) {
+            rebuild.remove_node(name);
+        }
+        rebuild.schedule()
+    }
+
+    /// Resume from a checkpoint: schedule the `frontier` tasks and everything downstream of them.
+    /// The frontier is ready at time 0 whatever it depends on, tasks upstream of it are left out.
+    /// Unknown names are `NoTaskFound`
+    pub fn schedule_from_frontier(
+        &self,
+        frontier: &[&str],
+    ) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let downstream = self.reachable_from(frontier)?;
+
+        let mut resumed = self.detached_copy();
+        for name in self.tasks.keys().filter(|name| !downstream.contains(*name)) {
+            resumed.remove_node(name);
+        }
+        for name in frontier {
+            resumed.set_dependencies(name, vec![])?;
+        }
+        resumed.schedule()
+    }
+
+    /// Tasks whose interval `[start, finish)` in the single-threaded schedule overlaps the
+    /// window `[start, end)`, in start order. A zero-length task counts if it sits in the window
+    pub fn tasks_in_window(&self, start: u32, end: u32) -> Result<Vec<String>, ScheduleError> {
+        Ok(self
+            .schedule_sorted_by_start()?
+            .into_iter()
+            .filter(|task| {
+                task.start < end
+                    && (start < task.finish() || task.duration == 0 && start <= task.start)
+            })
+            .map(|task| task.name)
+            .collect())
+    }
+
+    /// Copy of the graph without the recompute hook or recording, for what-if analysis
+    /// that must not notify anyone
+    pub(crate) fn detached_copy(&self) -> TaskScheduler {
+        TaskScheduler {
+            tasks: self.tasks.clone(),
+            first_level_dep: self.first_level_dep.clone(),
+            dependents: self.dependents.clone(),
+            resource_capacities: self.resource_capacities.clone(),
+            recompute_hook: None,
+            recording: None,
+            topo_order: None,
+            max_traversal_depth: self.max_traversal_depth,
+        }
+    }
+
+    /// Single machine scheduling core.
+    /// `pick` is given the currently ready tasks (in the order they became ready)
+    /// and returns the index of the one to run next.
+    /// `visitor` is told about every task as it gets placed and every edge as it gets relaxed
+    pub(crate) fn schedule_with_picker<
+        P: FnMut(&[String]) -> usize,
+        V: ScheduleVisitor + ?Sized,
+    >(
+        &self,
+        pick: P,
+        visitor: &mut V,
+    ) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
+        self.schedule_single(&U32Clock, pick, visitor, &[])
+    }
+
+    /// `schedule_with_picker` on the timeline of `clock`, where no task may occupy
+    /// the machine during any of the `[start, end)` `blackouts`
+    pub(crate) fn schedule_single<
+        C: Clock,
+        P: FnMut(&[String]) -> usize,
+        V: ScheduleVisitor<C::Instant> + ?Sized,
+    >(
+        &self,
+        clock: &C,
+        pick: P,
+        visitor: &mut V,
+        blackouts: &[(C::Instant, C::Instant)],
+    ) -> Result<Vec<(String, C::Instant, u32)>, ScheduleError> {
+        SingleRun::new(self, clock, pick, visitor, blackouts).collect()
+    }
+}
+
+/// One run of the single machine core, placing a task per `next`.
+/// The last item is an error if the run can't finish, nothing follows it
+pub(crate) struct SingleRun<'a, C: Clock, P, V> {
+    scheduler: &'a TaskScheduler,
+    clock: &'a C,
+    pick: P,
+    visitor: V,
+    blackouts: &'a [(C::Instant, C::Instant)],
+    zero_in_degree: VecDeque<String>,
+    in_degree: HashMap<String, usize>,
+    or_groups: OrGroups<'a>,
+    finish: HashMap<String, C::Instant>,
+    time: C::Instant,
+    /// Least time between two tasks taking the machine, see `with_min_gap`
+    min_gap: u32,
+    last_start: Option<C::Instant>,
+    /// Earliest start of some tasks, see `with_releases`
+    releases: Option<&'a HashMap<String, u32>>,
+    placed: usize,
+    done: bool,
+}
+
+impl<'a, C, P, V> SingleRun<'a, C, P, V>
+where
+    C: Clock,
+    P: FnMut(&[String]) -> usize,
+    V: ScheduleVisitor<C::Instant>,
+{
+    pub(crate) fn new(
+        scheduler: &'a TaskScheduler,
+        clock: &'a C,
+        pick: P,
+        visitor: V,
+        blackouts: &'a [(C::Instant, C::Instant)],
+    ) -> Self {
+        let mut zero_in_degree: VecDeque<String> = VecDeque::new();
+        let in_degree = scheduler.ready_degrees();
+
         // Collect all the task, that have 0 degree
         // i,e it does not have to wait for any other task to run
         // This can be a most bottom task in dependency graph or a task with with dependency
@@ -85,55 +1305,58 @@ impl TaskScheduler {
             }
         }
 
+        SingleRun {
+            scheduler,
+            clock,
+            pick,
+            visitor,
+            blackouts,
+            zero_in_degree,
+            in_degree,
+            or_groups: OrGroups::new(&scheduler.tasks),
+            finish: HashMap::new(),
+            time: clock.origin(),
+            min_gap: 0,
+            last_start: None,
+            releases: None,
+            placed: 0,
+            done: false,
+        }
+    }
+
+    /// Keep every task from taking the machine sooner than `gap` after the previous one did
+    pub(crate) fn with_min_gap(self, gap: u32) -> Self {
+        SingleRun {
+            min_gap: gap,
+            ..self
+        }
+    }
+
+    /// Keep the tasks in `releases` from taking the machine before their time
+    pub(crate) fn with_releases(self, releases: &'a HashMap<String, u32>) -> Self {
+        SingleRun {
+            releases: Some(releases),
+            ..self
+        }
+    }
+
+    fn step(&mut self) -> Option<<Self as Iterator>::Item> {
         // Loop though every task that does not have any dependency.
         // i.e loop from bottom of dependency graph
-        while let Some(task_name) = zero_in_degree.pop_front() {
-            if let Some(task) = self.tasks.get(&task_name) {
-                // We can push directly to the final order for no-dependency tasks
-                // this is the section where we add what need to be done exactly
-                order.push((task_name.clone(), time, task.duration));
-                // TODO: do_something();
-
-                // any task following have to wait for this task to finish.
-                // So add that timeline
-                //
-                // TODO:
-                // this assumes the single threading-like behavoiur of executing machine.
-                // i.e we have to wait for executing machine to execute current task
-                // even if next task is not dependent on current task
-                // In practical system,
-                // this can be changed to multi-threaded like behaviour
-                // i.e if current task is not dependency of next task, run next task
-                // in sepearte context ( thread )
-                time += task.duration;
-
-                // Get all the tasks which where dependent on this task
-                // since this task is complete,
-                // we can now execute other task that were directly depending on this task
-                if let Some(neighbors) = self.dependents.get(&task_name) {
-                    for neighbor in neighbors {
-                        if let Some(degree) = in_degree.get_mut(neighbor) {
-                            // since we completed the task which was a dependency of neighbour
-                            // we can reduce's reighbour's dependency degree by 1
-                            *degree -= 1;
-                            // check if new depenency degree is 0
-                            // if so, it means that neighbour task is no longer dependent on any
-                            // other ( i.e it's all dependencies are executed already )
-                            // so we can run it. Add this to zero_in_degree variable to preserve
-                            // order in next iteration
-                            if *degree == 0 {
-                                zero_in_degree.push_back(neighbor.clone());
-                            }
-                        }
-                    }
-                }
+        while !self.zero_in_degree.is_empty() {
+            let index = (self.pick)(self.zero_in_degree.make_contiguous());
+            let Some(task_name) = self.zero_in_degree.remove(index) else {
+                break;
+            };
+            if let Some(task) = self.scheduler.tasks.get(&task_name) {
+                return Some(self.place(task, task_name));
             }
         }
 
-        // all scheduled task are added in order variable
+        // all scheduled task are counted in placed variable
         // and all initial task are stull preserved as-is in self.tasks variable
         // compare the size of those two
-        match order.len().cmp(&self.tasks.len()) {
+        match self.placed.cmp(&self.scheduler.tasks.len()) {
             // Number of task scheduled is less than the initial task count
             // This means some task were not scheduled
             // According to above implementation,
@@ -142,34 +1365,187 @@ impl TaskScheduler {
             // This can only happen when the dependency is cyclic then the dependency degree will:
             // =1: dependent to itself
             // >1: dependent to a task which in turn along the way depends on this task
-            std::cmp::Ordering::Less => Err(ScheduleError::CycleDetected),
+            Ordering::Less => Some(Err(ScheduleError::CycleDetected)),
 
             // This means that all task were scheduled,
             // this is ok result in our case
-            std::cmp::Ordering::Equal => Ok(order),
+            Ordering::Equal => None,
 
             // This means some tasks were scheduled more than once
             // This will never occur in our case ( single-threaded like environment )
             // so we skip this
-            std::cmp::Ordering::Greater => {
+            Ordering::Greater => {
                 unreachable!()
             }
         }
     }
-}
-
-#[test]
-fn test_non_cylce() {
-    let mut scheduler = TaskScheduler::new();
-    scheduler.add_task("D", vec!["B", "C"], 4);
-    scheduler.add_task("A", vec![], 3);
-    scheduler.add_task("B", vec!["A"], 2);
-    scheduler.add_task("C", vec!["A"], 1);
 
-    let schedule = scheduler.schedule_tasks();
-    assert_eq!(
-        schedule,
-        Ok(vec![
+    fn place(
+        &mut self,
+        task: &'a Task,
+        task_name: String,
+    ) -> Result<(String, C::Instant, u32), ScheduleError> {
+        let clock = self.clock;
+        let advance = |from: C::Instant, by: u32| {
+            clock
+                .advance(from, by.into())
+                .ok_or(ScheduleError::DurationOverflow)
+        };
+        let finish = &self.finish;
+        // machine is free at `time`, but the task also has to wait
+        // for the lag after each of its dependencies
+        let start = task
+            .dependencies
+            .iter()
+            .filter_map(|dep| finish.get(dep).map(|&end| advance(end, task.lag(dep))))
+            .try_fold(self.time, |latest, end| end.map(|end| latest.max(end)))?;
+        // and for the first task of each OR-group to finish
+        let start = task
+            .or_groups
+            .iter()
+            .filter_map(|group| group.iter().filter_map(|member| finish.get(member)).min())
+            .fold(start, |latest, &end| latest.max(end));
+        // and for the cooldown since the previous task took the machine
+        let start = match self.last_start {
+            Some(last) => start.max(advance(last, self.min_gap)?),
+            None => start,
+        };
+        // and for its release
+        let start = match self.releases.and_then(|releases| releases.get(&task_name)) {
+            Some(&release) => start.max(advance(clock.origin(), release)?),
+            None => start,
+        };
+        // a pinned task starts exactly at its time, the machine idles until then.
+        // if the machine or a dependency is still busy by then, it can't be honored
+        let fixed = match task.fixed_start {
+            Some(fixed) => Some(advance(clock.origin(), fixed)?),
+            None => None,
+        };
+        let start = match fixed {
+            Some(fixed) if fixed < start => {
+                return Err(ScheduleError::ConstraintViolation(task_name));
+            }
+            Some(fixed) => fixed,
+            None => start,
+        };
+        // wait out every blackout the whole occupied interval would overlap.
+        // even a zero-length task can't run inside one
+        let occupied = task
+            .setup
+            .saturating_add(task.duration)
+            .saturating_add(task.teardown)
+            .max(1);
+        let mut pushed = start;
+        while let Some(&(_, end)) = self.blackouts.iter().find(|&&(from, to)| {
+            pushed < to && advance(pushed, occupied).map_or(true, |until| from < until)
+        }) {
+            pushed = end;
+        }
+        if task.fixed_start.is_some() && pushed != start {
+            return Err(ScheduleError::ConstraintViolation(task_name));
+        }
+        let start = pushed;
+        self.last_start = Some(start);
+        // the machine is taken from `start`, but the real work only begins after setup
+        let start = advance(start, task.setup)?;
+
+        // We can hand out the task directly, its place in the final order is known
+        // this is the section where we add what need to be done exactly
+        self.visitor.on_task(task, start);
+        // TODO: do_something();
+
+        // any task following have to wait for this task to finish.
+        // So add that timeline
+        //
+        // TODO:
+        // this assumes the single threading-like behavoiur of executing machine.
+        // i.e we have to wait for executing machine to execute current task
+        // even if next task is not dependent on current task
+        // In practical system,
+        // this can be changed to multi-threaded like behaviour
+        // i.e if current task is not dependency of next task, run next task
+        // in sepearte context ( thread )
+        self.time = advance(advance(start, task.duration)?, task.teardown)?;
+        self.finish.insert(task_name.clone(), self.time);
+
+        // Get all the tasks which where dependent on this task
+        // since this task is complete,
+        // we can now execute other task that were directly depending on this task
+        if let Some(neighbors) = self.scheduler.dependents.get(&task_name) {
+            for neighbor in neighbors {
+                if let Some(degree) = self.in_degree.get_mut(neighbor) {
+                    // since we completed the task which was a dependency of neighbour
+                    // we can reduce's reighbour's dependency degree by 1
+                    *degree -= 1;
+                    self.visitor.on_edge_relaxed(&task_name, neighbor);
+                    // check if new depenency degree is 0
+                    // if so, it means that neighbour task is no longer dependent on any
+                    // other ( i.e it's all dependencies are executed already )
+                    // so we can run it. Add this to zero_in_degree variable to preserve
+                    // order in next iteration
+                    if *degree == 0 {
+                        self.zero_in_degree.push_back(neighbor.clone());
+                    }
+                }
+            }
+        }
+        for waiting in self.or_groups.finished(&task_name) {
+            if let Some(degree) = self.in_degree.get_mut(waiting) {
+                *degree -= 1;
+                self.visitor.on_edge_relaxed(&task_name, waiting);
+                if *degree == 0 {
+                    self.zero_in_degree.push_back(waiting.to_string());
+                }
+            }
+        }
+
+        self.placed += 1;
+        Ok((task_name, start, task.duration))
+    }
+}
+
+impl<C, P, V> Iterator for SingleRun<'_, C, P, V>
+where
+    C: Clock,
+    P: FnMut(&[String]) -> usize,
+    V: ScheduleVisitor<C::Instant>,
+{
+    type Item = Result<(String, C::Instant, u32), ScheduleError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = self.step();
+        self.done = !matches!(item, Some(Ok(_)));
+        item
+    }
+}
+
+/// The A/B/C/D graph used across the tests:
+/// A runs first, B and C depend on A and D depends on both B and C
+#[cfg(test)]
+pub(crate) fn example_scheduler() -> TaskScheduler {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("D", vec!["B", "C"], 4);
+    scheduler.add_task("A", vec![], 3);
+    scheduler.add_task("B", vec!["A"], 2);
+    scheduler.add_task("C", vec!["A"], 1);
+    scheduler
+}
+
+#[test]
+fn test_non_cylce() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("D", vec!["B", "C"], 4);
+    scheduler.add_task("A", vec![], 3);
+    scheduler.add_task("B", vec!["A"], 2);
+    scheduler.add_task("C", vec!["A"], 1);
+
+    let schedule = scheduler.schedule_tasks();
+    assert_eq!(
+        schedule,
+        Ok(vec![
             ("A".to_string(), 0, 3),
             ("B".to_string(), 3, 2),
             ("C".to_string(), 5, 1),
@@ -188,3 +1564,723 @@ fn test_cycle_detection() {
     let schedule = scheduler.schedule_tasks();
     assert_eq!(schedule, Err(ScheduleError::CycleDetected));
 }
+
+#[test]
+fn test_guard_rejects_zero_duration() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 3);
+    scheduler.add_task("B", vec!["A"], 0);
+
+    let no_zero_duration =
+        |s: &TaskScheduler| match s.tasks.values().find(|task| task.duration == 0) {
+            Some(task) => Err(format!("task {} has zero duration", task.name)),
+            None => Ok(()),
+        };
+
+    let schedule = scheduler.schedule_with_guard(no_zero_duration);
+    assert_eq!(
+        schedule,
+        Err(ScheduleError::GuardRejected(
+            "task B has zero duration".to_string()
+        ))
+    );
+
+    // once the offending task is fixed the guard lets scheduling proceed
+    scheduler.tasks.get_mut("B").unwrap().duration = 2;
+    assert_eq!(
+        scheduler.schedule_with_guard(no_zero_duration),
+        scheduler.schedule_tasks()
+    );
+}
+
+#[test]
+fn test_extend_fallible_stops_at_duplicate() {
+    let mut scheduler = TaskScheduler::new();
+    let stream = vec![
+        ("A".to_string(), vec![], 3),
+        ("B".to_string(), vec!["A".to_string()], 2),
+        ("A".to_string(), vec![], 1),
+        ("C".to_string(), vec!["A".to_string()], 1),
+    ];
+
+    assert_eq!(
+        scheduler.extend_fallible(stream),
+        Err(AddError::DuplicateTask("A".to_string()))
+    );
+    // everything before the duplicate is kept, nothing after it was read
+    assert_eq!(scheduler.tasks.len(), 2);
+    assert_eq!(scheduler.tasks["A"].duration, 3);
+    assert!(!scheduler.tasks.contains_key("C"));
+
+    let rest = vec![("C".to_string(), vec!["A".to_string()], 1)];
+    assert_eq!(scheduler.extend_fallible(rest), Ok(1));
+}
+
+#[test]
+fn test_dependency_lag() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler
+        .add_task_with_lags("D", vec![("B", 2), ("C", 0)], 4)
+        .unwrap();
+    scheduler.add_task("A", vec![], 3);
+    scheduler.add_task("B", vec!["A"], 2);
+    scheduler.add_task("C", vec!["A"], 1);
+
+    // B finishes at 5, so D can't start before 5 + 2 even though the machine is free at 6
+    let schedule = scheduler.schedule_tasks().unwrap();
+    assert_eq!(schedule[3], ("D".to_string(), 7, 4));
+    assert_eq!(scheduler.tasks["D"].lag("C"), 0);
+
+    // one mutation per task, with the lags already in it
+    scheduler.enable_recording();
+    scheduler
+        .add_task_with_lags("E", vec![("D", 1)], 1)
+        .unwrap();
+    assert_eq!(scheduler.mutations().len(), 5);
+    assert_eq!(scheduler.replay().unwrap(), scheduler);
+}
+
+#[test]
+fn test_add_recurring() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("setup", vec![], 2);
+    scheduler
+        .add_recurring("A", vec!["setup"], 3, 5, 3)
+        .unwrap();
+    assert_eq!(scheduler.tasks.len(), 4);
+    assert_eq!(scheduler.tasks["A#2"].dependencies, ["A#1"]);
+
+    let schedule = scheduler.schedule_tasks().unwrap();
+    assert_eq!(
+        schedule,
+        [
+            ("setup".to_string(), 0, 2),
+            ("A#0".to_string(), 2, 3),
+            ("A#1".to_string(), 7, 3),
+            ("A#2".to_string(), 12, 3),
+        ]
+    );
+
+    assert_eq!(
+        scheduler.add_recurring("A", vec![], 1, 1, 5),
+        Err(AddError::DuplicateTask("A#0".to_string()))
+    );
+    assert_eq!(scheduler.tasks.len(), 4);
+}
+
+#[test]
+fn test_tasks_macro() {
+    let scheduler = tasks! {
+        "D" => ["B", "C"] : 4;
+        "A" => [] : 3;
+        "B" => ["A"] : 2;
+        "C" => ["A"] : 1;
+    }
+    .unwrap();
+    assert_eq!(
+        scheduler.schedule_tasks(),
+        example_scheduler().schedule_tasks()
+    );
+
+    let duplicate = tasks! {
+        "A" => [] : 3;
+        "A" => [] : 1;
+    };
+    assert_eq!(
+        duplicate.map(|_| ()),
+        Err(AddError::DuplicateTask("A".to_string()))
+    );
+}
+
+#[test]
+fn test_schedule_min_max_lateness() {
+    let mut scheduler = example_scheduler();
+    scheduler.set_deadline("B", Some(9)).unwrap();
+    scheduler.set_deadline("C", Some(4)).unwrap();
+    scheduler.set_deadline("D", Some(10)).unwrap();
+    let max_lateness = |schedule: &[ScheduledTask]| {
+        schedule
+            .iter()
+            .filter_map(|t| Some(t.finish() as i64 - scheduler.tasks[&t.name].deadline? as i64))
+            .max()
+    };
+
+    // the default order runs B first, so C is 2 late
+    assert_eq!(max_lateness(&scheduler.schedule().unwrap()), Some(2));
+    let schedule = scheduler.schedule_min_max_lateness().unwrap();
+    let order: Vec<&str> = schedule.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(order, vec!["A", "C", "B", "D"]);
+    assert_eq!(max_lateness(&schedule), Some(0));
+
+    scheduler.add_task("E", vec!["E"], 1);
+    assert_eq!(
+        scheduler.schedule_min_max_lateness(),
+        Err(ScheduleError::CycleDetected)
+    );
+}
+
+#[test]
+fn test_schedule_edf() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("Late", vec![], 2);
+    scheduler.add_task("Soon", vec![], 2);
+    scheduler.add_task("Whenever", vec![], 2);
+    scheduler.set_deadline("Late", Some(10)).unwrap();
+    scheduler.set_deadline("Soon", Some(2)).unwrap();
+
+    let names: Vec<String> = scheduler
+        .schedule_edf()
+        .unwrap()
+        .into_iter()
+        .map(|task| task.name)
+        .collect();
+    assert_eq!(names, vec!["Soon", "Late", "Whenever"]);
+}
+
+#[test]
+fn test_add_task_strict() {
+    let mut scheduler = TaskScheduler::new();
+    assert_eq!(
+        scheduler.add_task_strict("B", vec!["A"], 2),
+        Err(AddError::MissingDependency("A".to_string()))
+    );
+    assert!(scheduler.tasks.is_empty());
+
+    scheduler.add_task_strict("A", vec![], 3).unwrap();
+    scheduler.add_task_strict("B", vec!["A"], 2).unwrap();
+    assert_eq!(
+        scheduler.add_task_strict("A", vec![], 1),
+        Err(AddError::DuplicateTask("A".to_string()))
+    );
+    assert_eq!(
+        scheduler.topological_order(),
+        Ok(vec!["A".to_string(), "B".to_string()])
+    );
+}
+
+#[test]
+fn test_schedule_f64() {
+    let scheduler = example_scheduler();
+    let floats = scheduler.schedule_f64().unwrap();
+    let integers = scheduler.schedule_tasks().unwrap();
+
+    assert_eq!(floats.len(), integers.len());
+    for ((name, start, duration), (int_name, int_start, int_duration)) in
+        floats.iter().zip(&integers)
+    {
+        assert_eq!(name, int_name);
+        assert_eq!(*start, *int_start as f64);
+        assert_eq!(*duration, *int_duration as f64);
+    }
+}
+
+#[test]
+fn test_into_tasks() {
+    let tasks = example_scheduler().into_tasks();
+    assert_eq!(tasks.len(), 4);
+    assert_eq!(tasks["D"].dependencies, vec!["B", "C"]);
+
+    let names: Vec<String> = example_scheduler()
+        .into_sorted_vec()
+        .into_iter()
+        .map(|task| task.name)
+        .collect();
+    assert_eq!(names, vec!["A", "B", "C", "D"]);
+}
+
+#[test]
+fn test_is_schedule_deterministic() {
+    let mut chain = TaskScheduler::new();
+    chain.add_task("A", vec![], 1);
+    chain.add_task("B", vec!["A"], 2);
+    chain.add_task("C", vec!["B"], 3);
+    assert_eq!(chain.is_schedule_deterministic(), Ok(true));
+
+    let mut wide = TaskScheduler::new();
+    for (name, duration) in [("A", 1), ("B", 2), ("C", 3), ("D", 4)] {
+        wide.add_task(name, vec![], duration);
+    }
+    assert_eq!(wide.is_schedule_deterministic(), Ok(false));
+}
+
+#[test]
+fn test_rebuild_indices() {
+    let mut scheduler = example_scheduler();
+    // bypass the bookkeeping: D now only waits for C
+    scheduler.tasks.get_mut("D").unwrap().dependencies = vec!["C".to_string()];
+    scheduler.rebuild_indices();
+
+    assert_eq!(scheduler.first_level_dep["D"], 1);
+    assert!(!scheduler.dependents.contains_key("B"));
+    assert_eq!(scheduler.dependents["C"], vec!["D"]);
+    let d = scheduler.schedule_asap().unwrap().pop().unwrap();
+    assert_eq!((d.name.as_str(), d.start), ("D", 4));
+}
+
+#[test]
+fn test_schedule_with_blackouts() {
+    let scheduler = example_scheduler();
+    let starts = |schedule: Vec<ScheduledTask>| -> Vec<(String, u32)> {
+        schedule.into_iter().map(|t| (t.name, t.start)).collect()
+    };
+
+    // B would run 3..5, right into the 4..6 blackout
+    let schedule = scheduler.schedule_with_blackouts(&[(4, 6)]).unwrap();
+    assert_eq!(
+        starts(schedule),
+        vec![
+            ("A".to_string(), 0),
+            ("B".to_string(), 6),
+            ("C".to_string(), 8),
+            ("D".to_string(), 9),
+        ]
+    );
+    assert_eq!(
+        scheduler.schedule_with_blackouts(&[]).unwrap(),
+        scheduler.schedule().unwrap()
+    );
+}
+
+#[test]
+fn test_schedule_dirty() {
+    let scheduler = example_scheduler();
+    let dirty =
+        |names: &[&str]| -> HashSet<String> { names.iter().map(|n| n.to_string()).collect() };
+    let names = |schedule: Vec<ScheduledTask>| -> Vec<String> {
+        schedule.into_iter().map(|t| t.name).collect()
+    };
+
+    // everything descends from A
+    assert_eq!(scheduler.schedule_dirty(&dirty(&["A"])).unwrap().len(), 4);
+
+    let rebuild = scheduler.schedule_dirty(&dirty(&["C"])).unwrap();
+    assert_eq!(rebuild[0].start, 0);
+    assert_eq!(names(rebuild), vec!["C", "D"]);
+
+    assert!(scheduler.schedule_dirty(&dirty(&[])).unwrap().is_empty());
+    assert_eq!(
+        scheduler.schedule_dirty(&dirty(&["X"])),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
+
+#[test]
+fn test_schedule_lookup() {
+    let schedule = Schedule::from(example_scheduler().schedule().unwrap());
+    let d = schedule.get("D").unwrap();
+    assert_eq!(d.start, 6);
+    assert_eq!(d.finish(), 10);
+    assert!(schedule.get("X").is_none());
+    assert_eq!(schedule.len(), 4);
+    assert_eq!(schedule[0].name, "A");
+}
+
+#[test]
+fn test_scheduled_task_finish() {
+    let task = ScheduledTask::from(("A".to_string(), 3, 2));
+    assert_eq!(task.finish(), 5);
+    let task = ScheduledTask::from(("A".to_string(), u32::MAX - 1, 2));
+    assert_eq!(task.finish(), u32::MAX);
+}
+
+#[test]
+fn test_schedule_with_factor() {
+    let scheduler = example_scheduler();
+    let makespan =
+        |schedule: &[ScheduledTask]| schedule.iter().map(|t| t.start + t.duration).max().unwrap();
+    assert_eq!(makespan(&scheduler.schedule().unwrap()), 10);
+    assert_eq!(makespan(&scheduler.schedule_with_factor(1.5).unwrap()), 15);
+    // stored durations are untouched
+    assert_eq!(scheduler.tasks["A"].duration, 3);
+
+    assert_eq!(
+        scheduler.schedule_with_factor(2e9),
+        Err(ScheduleError::DurationOverflow)
+    );
+    assert_eq!(makespan(&scheduler.schedule_with_factor(-1.0).unwrap()), 0);
+}
+
+#[test]
+fn test_schedule_with_factor_rounded() {
+    let scheduler = example_scheduler();
+    let durations = |rounding| -> Vec<(String, u32)> {
+        let mut durations: Vec<(String, u32)> = scheduler
+            .schedule_with_factor_rounded(1.5, rounding)
+            .unwrap()
+            .into_iter()
+            .map(|task| (task.name, task.duration))
+            .collect();
+        durations.sort();
+        durations
+    };
+    let expect = |a, b, c, d| {
+        vec![
+            ("A".to_string(), a),
+            ("B".to_string(), b),
+            ("C".to_string(), c),
+            ("D".to_string(), d),
+        ]
+    };
+    // 4.5, 3, 1.5 and 6
+    assert_eq!(durations(RoundingMode::Floor), expect(4, 3, 1, 6));
+    assert_eq!(durations(RoundingMode::Ceil), expect(5, 3, 2, 6));
+    assert_eq!(durations(RoundingMode::Nearest), expect(4, 3, 2, 6));
+    assert_eq!(
+        scheduler.schedule_with_factor(1.5),
+        scheduler.schedule_with_factor_rounded(1.5, RoundingMode::Nearest)
+    );
+}
+
+#[test]
+fn test_schedule_fast() {
+    let starts = |scheduler: &TaskScheduler| -> HashMap<String, u32> {
+        scheduler
+            .schedule_tasks()
+            .unwrap()
+            .into_iter()
+            .map(|(name, start, _)| (name, start))
+            .collect()
+    };
+    let scheduler = example_scheduler();
+    assert_eq!(scheduler.schedule_fast().unwrap(), starts(&scheduler));
+
+    // large synthetic graph: 100 chains of 500 tasks, each also waiting on the previous chain
+    let mut large = TaskScheduler::new();
+    for chain in 0..100 {
+        for link in 0..500 {
+            let mut deps = Vec::new();
+            if link > 0 {
+                deps.push(format!("{chain}-{}", link - 1));
+            }
+            if chain > 0 {
+                deps.push(format!("{}-{link}", chain - 1));
+            }
+            let deps = deps.iter().map(String::as_str).collect();
+            large.add_task(&format!("{chain}-{link}"), deps, (chain + link) % 7);
+        }
+    }
+    assert_eq!(large.schedule_fast().unwrap(), starts(&large));
+
+    let mut alternatives = TaskScheduler::new();
+    alternatives.add_task("slow", vec![], 1);
+    alternatives
+        .add_task_with_or_deps("X1", vec![], vec![vec!["slow"]], 1)
+        .unwrap();
+    assert_eq!(
+        alternatives.schedule_fast().unwrap(),
+        HashMap::from([("slow".to_string(), 0), ("X1".to_string(), 1)])
+    );
+
+    let mut cyclic = example_scheduler();
+    cyclic.add_task("X", vec!["X"], 1);
+    assert_eq!(cyclic.schedule_fast(), Err(ScheduleError::CycleDetected));
+}
+
+#[test]
+fn test_duration_overflow() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], u32::MAX / 2 + 1);
+    scheduler.add_task("B", vec!["A"], u32::MAX / 2 + 1);
+    assert_eq!(
+        scheduler.schedule_tasks(),
+        Err(ScheduleError::DurationOverflow)
+    );
+
+    scheduler.set_duration("B", u32::MAX / 2).unwrap();
+    assert!(scheduler.schedule_tasks().is_ok());
+}
+
+#[test]
+fn test_duration_overflow_parallel_and_asap() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], u32::MAX - 1);
+    scheduler
+        .add_task_with_lags("B", vec![("A", 5)], 0)
+        .unwrap();
+    assert_eq!(
+        scheduler.schedule_tasks_parallel(2),
+        Err(ScheduleError::DurationOverflow)
+    );
+    assert_eq!(
+        scheduler.schedule_asap().map(|_| ()),
+        Err(ScheduleError::DurationOverflow)
+    );
+
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], u32::MAX / 2 + 1);
+    scheduler.add_task("B", vec!["A"], u32::MAX / 2 + 1);
+    assert_eq!(
+        scheduler.schedule_tasks_parallel(2),
+        Err(ScheduleError::DurationOverflow)
+    );
+    assert_eq!(
+        scheduler.schedule_asap().map(|_| ()),
+        Err(ScheduleError::DurationOverflow)
+    );
+    assert_eq!(
+        scheduler.schedule_tasks_parallel_with_ramp_up(1, 1),
+        Err(ScheduleError::DurationOverflow)
+    );
+
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], u32::MAX);
+    assert_eq!(
+        scheduler.schedule_tasks_parallel_with_ramp_up(1, 1),
+        Err(ScheduleError::DurationOverflow)
+    );
+}
+
+#[test]
+fn test_overflow_mode() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], u32::MAX / 2 + 1);
+    scheduler.add_task("B", vec!["A"], u32::MAX / 2 + 1);
+    scheduler.add_task("C", vec!["B"], 1);
+
+    assert_eq!(ScheduleConfig::default().overflow, OverflowMode::Error);
+    assert_eq!(
+        scheduler.schedule_with_config(&ScheduleConfig::default()),
+        Err(ScheduleError::DurationOverflow)
+    );
+
+    let saturate = ScheduleConfig {
+        overflow: OverflowMode::Saturate,
+    };
+    let schedule = scheduler.schedule_with_config(&saturate).unwrap();
+    assert_eq!(
+        schedule[1],
+        ("B".to_string(), u32::MAX / 2 + 1, u32::MAX / 2 + 1)
+    );
+    assert_eq!(schedule[2], ("C".to_string(), u32::MAX, 1));
+}
+
+#[test]
+fn test_setup_and_teardown() {
+    let mut scheduler = example_scheduler();
+    scheduler.set_overhead("B", 1, 2).unwrap();
+
+    let schedule = scheduler.schedule().unwrap();
+    let start_of = |name: &str| schedule.iter().find(|t| t.name == name).unwrap().start;
+    // B works 4..6 after setup, then tears down until 8 before C (and later D) can go
+    assert_eq!(start_of("B"), 4);
+    assert_eq!(start_of("C"), 8);
+    assert_eq!(start_of("D"), 9);
+
+    assert_eq!(
+        scheduler.set_overhead("missing", 1, 1),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
+
+#[test]
+fn test_fixed_start() {
+    let mut scheduler = example_scheduler();
+    scheduler.set_fixed_start("D", Some(20)).unwrap();
+
+    assert_eq!(
+        scheduler.schedule_tasks(),
+        Ok(vec![
+            ("A".to_string(), 0, 3),
+            ("B".to_string(), 3, 2),
+            ("C".to_string(), 5, 1),
+            ("D".to_string(), 20, 4),
+        ])
+    );
+
+    // B and C can't both be done by 4
+    scheduler.set_fixed_start("D", Some(4)).unwrap();
+    assert_eq!(
+        scheduler.schedule_tasks(),
+        Err(ScheduleError::ConstraintViolation("D".to_string()))
+    );
+}
+
+#[test]
+fn test_schedule_seeded() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("Root", vec![], 1);
+    for name in ["A", "B", "C", "D", "E"] {
+        scheduler.add_task(name, vec!["Root"], 1);
+    }
+    let names = |schedule: Vec<ScheduledTask>| -> Vec<String> {
+        schedule.into_iter().map(|task| task.name).collect()
+    };
+
+    assert_eq!(
+        scheduler.schedule_seeded(7).unwrap(),
+        scheduler.schedule_seeded(7).unwrap()
+    );
+
+    let orders: std::collections::HashSet<Vec<String>> = (0..20)
+        .map(|seed| names(scheduler.schedule_seeded(seed).unwrap()))
+        .collect();
+    assert!(orders.len() > 1);
+    // whatever the seed, the root always goes first
+    assert!(orders.iter().all(|order| order[0] == "Root"));
+}
+
+#[test]
+fn test_tasks_in_window() {
+    let scheduler = example_scheduler();
+    // A ends right as the window opens and D starts right as it closes
+    assert_eq!(
+        scheduler.tasks_in_window(3, 6),
+        Ok(vec!["B".to_string(), "C".to_string()])
+    );
+    assert_eq!(scheduler.tasks_in_window(0, 10).unwrap().len(), 4);
+    assert_eq!(scheduler.tasks_in_window(10, 20), Ok(vec![]));
+}
+
+#[test]
+fn test_schedule_from_frontier() {
+    let scheduler = example_scheduler();
+    let schedule = scheduler.schedule_from_frontier(&["B", "C"]).unwrap();
+
+    let mut names: Vec<&str> = schedule.iter().map(|task| task.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["B", "C", "D"]);
+    // B and C no longer wait for A, so D can start after their 3 units
+    let d = schedule.iter().find(|task| task.name == "D").unwrap();
+    assert_eq!(d.start, 3);
+
+    assert_eq!(
+        scheduler.schedule_from_frontier(&["X"]),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
+
+#[test]
+fn test_set_durations() {
+    let mut scheduler = example_scheduler();
+    scheduler
+        .set_durations(&[("A", 1), ("B", 1), ("D", 2)])
+        .unwrap();
+    assert_eq!(
+        scheduler.schedule_tasks().unwrap(),
+        vec![
+            ("A".to_string(), 0, 1),
+            ("B".to_string(), 1, 1),
+            ("C".to_string(), 2, 1),
+            ("D".to_string(), 3, 2),
+        ]
+    );
+
+    // one recorded mutation and one hook call for the whole batch
+    let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = std::sync::Arc::clone(&calls);
+    scheduler.set_recompute_hook(move |_| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    });
+    scheduler.enable_recording();
+    scheduler.set_durations(&[("A", 2), ("C", 3)]).unwrap();
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert_eq!(
+        scheduler.mutations().last(),
+        Some(&Mutation::SetDurations(vec![
+            ("A".to_string(), 2),
+            ("C".to_string(), 3)
+        ]))
+    );
+    assert_eq!(scheduler.mutations().len(), 4 + 1);
+    assert_eq!(scheduler.replay().unwrap(), scheduler);
+}
+
+#[test]
+fn test_set_durations_is_atomic() {
+    let mut scheduler = example_scheduler();
+    assert_eq!(
+        scheduler.set_durations(&[("A", 1), ("missing", 1), ("D", 2)]),
+        Err(ScheduleError::NoTaskFound)
+    );
+    assert_eq!(scheduler, example_scheduler());
+}
+
+#[test]
+fn test_schedule_detailed() {
+    let detailed = example_scheduler().schedule_detailed().unwrap();
+    let wait = |name: &str| detailed.iter().find(|task| task.name == name).unwrap().wait;
+    assert_eq!(wait("A"), 0);
+    assert_eq!(wait("B"), 0);
+    // ready once A is done at 3, but the machine runs B until 5
+    assert_eq!(wait("C"), 2);
+    assert_eq!(wait("D"), 0);
+}
+
+#[test]
+fn test_schedule_min_weighted_completion() {
+    let mut scheduler = example_scheduler();
+    scheduler.set_weight("C", 5).unwrap();
+
+    let smith = scheduler.schedule_min_weighted_completion().unwrap();
+    let names: Vec<&str> = smith.iter().map(|task| task.name.as_str()).collect();
+    // C is short and heavy, so it goes before B
+    assert_eq!(names, vec!["A", "C", "B", "D"]);
+
+    let as_tuples: Vec<(String, u32, u32)> = smith
+        .into_iter()
+        .map(|task| (task.name, task.start, task.duration))
+        .collect();
+    let optimized = scheduler.weighted_completion_time_of(&as_tuples);
+    assert_eq!(optimized, 39);
+    assert!(optimized <= scheduler.weighted_completion_time().unwrap());
+}
+
+#[test]
+fn test_schedule_iter() {
+    let scheduler = example_scheduler();
+    let streamed: Vec<ScheduledTask> = scheduler.schedule_iter().unwrap().collect();
+    assert_eq!(streamed, scheduler.schedule().unwrap());
+
+    // the error comes before any entry
+    let mut scheduler = example_scheduler();
+    scheduler.add_task("E", vec!["missing"], 1);
+    assert_eq!(
+        scheduler.schedule_iter().err(),
+        Some(ScheduleError::CycleDetected)
+    );
+    let mut scheduler = example_scheduler();
+    scheduler.add_task("E", vec!["A"], 1);
+    scheduler.set_fixed_start("E", Some(0)).unwrap();
+    assert!(matches!(
+        scheduler.schedule_iter(),
+        Err(ScheduleError::ConstraintViolation(_))
+    ));
+}
+
+#[test]
+fn test_schedule_with_min_gap() {
+    let scheduler = example_scheduler();
+    let schedule = scheduler.schedule_with_min_gap(2).unwrap();
+    let starts: Vec<(&str, u32)> = schedule
+        .iter()
+        .map(|t| (t.name.as_str(), t.start))
+        .collect();
+    // C only takes 1, so D waits for the gap after C started instead of starting at 6
+    assert_eq!(starts, vec![("A", 0), ("B", 3), ("C", 5), ("D", 7)]);
+    assert!(schedule
+        .windows(2)
+        .all(|pair| pair[1].start >= pair[0].start + 2));
+
+    assert_eq!(scheduler.schedule_with_min_gap(0), scheduler.schedule());
+}
+
+#[test]
+fn test_completion_time_with_releases() {
+    let scheduler = example_scheduler();
+    assert_eq!(
+        scheduler.completion_time_with_releases(&HashMap::new()),
+        Ok(10)
+    );
+    // A is the only root, everything waits for it
+    let releases = HashMap::from([("A".to_string(), 5)]);
+    assert_eq!(scheduler.completion_time_with_releases(&releases), Ok(15));
+    // released before the machine gets to it anyway
+    let releases = HashMap::from([("D".to_string(), 4)]);
+    assert_eq!(scheduler.completion_time_with_releases(&releases), Ok(10));
+
+    let releases = HashMap::from([("X".to_string(), 1)]);
+    assert_eq!(
+        scheduler.completion_time_with_releases(&releases),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use crate::{RecomputeReason, ScheduleError, TaskScheduler};
+
+impl TaskScheduler {
+    /// Tag an existing task with `key => value`, replacing any previous value of `key`
+    pub fn set_metadata(
+        &mut self,
+        name: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.metadata.insert(key.to_string(), value.to_string());
+        self.record_task(name);
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Task names grouped by their value of metadata `key`, each group sorted.
+    /// Tasks without `key` are left out
+    pub fn group_by_metadata(&self, key: &str) -> HashMap<String, Vec<String>> {
+        let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+        for task in self.tasks.values() {
+            if let Some(value) = task.metadata.get(key) {
+                groups
+                    .entry(value.clone())
+                    .or_default()
+                    .push(task.name.clone());
+            }
+        }
+        for names in groups.values_mut() {
+            names.sort();
+        }
+        groups
+    }
+}
+
+#[test]
+fn test_group_by_metadata() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.set_metadata("A", "team", "infra").unwrap();
+    scheduler.set_metadata("B", "team", "web").unwrap();
+    scheduler.set_metadata("D", "team", "infra").unwrap();
+    scheduler.set_metadata("C", "owner", "sam").unwrap();
+
+    let groups = scheduler.group_by_metadata("team");
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups["infra"], vec!["A", "D"]);
+    assert_eq!(groups["web"], vec!["B"]);
+    assert!(scheduler.group_by_metadata("missing").is_empty());
+
+    assert_eq!(
+        scheduler.set_metadata("missing", "team", "web"),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
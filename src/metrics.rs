@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+
+use crate::{parallel::makespan_of, ScheduleError, Task, TaskScheduler};
+
+impl TaskScheduler {
+    /// Sum of all task durations, i.e the total amount of work.
+    /// Returned as `u64` since the sum of many `u32` durations can overflow `u32`
+    pub fn total_duration(&self) -> u64 {
+        self.tasks.values().map(|task| task.duration as u64).sum()
+    }
+
+    /// Mean task duration, `None` when there are no tasks
+    pub fn average_duration(&self) -> Option<f64> {
+        if self.tasks.is_empty() {
+            return None;
+        }
+        Some(self.total_duration() as f64 / self.tasks.len() as f64)
+    }
+
+    /// Task whose transitive dependents carry the most total work, along with that total.
+    /// Delaying it holds up the most downstream work. Ties go to the alphabetically first task
+    pub fn bottleneck(&self) -> Result<Option<(String, u32)>, ScheduleError> {
+        let mut best: Option<(String, u32)> = None;
+        for name in self.topological_order()? {
+            let downstream: u32 = self
+                .all_dependents(&name)?
+                .iter()
+                .map(|dependent| self.tasks[dependent].duration)
+                .sum();
+            let better = match &best {
+                Some((best_name, best_total)) => {
+                    (downstream, std::cmp::Reverse(&name))
+                        > (*best_total, std::cmp::Reverse(best_name))
+                }
+                None => true,
+            };
+            if better {
+                best = Some((name, downstream));
+            }
+        }
+        Ok(best)
+    }
+
+    /// Rough estimate of the heap bytes held by `tasks`, `first_level_dep` and `dependents`:
+    /// map slots at their current capacity, plus the strings and vectors they own.
+    /// Per-task maps (lags, resources, metadata) and allocator overhead are not counted,
+    /// so treat it as an approximation for dashboards, not an exact figure
+    pub fn memory_footprint(&self) -> usize {
+        use std::mem::size_of;
+
+        let strings = |list: &Vec<String>| -> usize {
+            list.capacity() * size_of::<String>() + list.iter().map(String::capacity).sum::<usize>()
+        };
+
+        let tasks = self.tasks.capacity() * (size_of::<String>() + size_of::<Task>())
+            + self
+                .tasks
+                .iter()
+                .map(|(key, task)| {
+                    key.capacity() + task.name.capacity() + strings(&task.dependencies)
+                })
+                .sum::<usize>();
+        let first_level_dep = self.first_level_dep.capacity()
+            * (size_of::<String>() + size_of::<usize>())
+            + self
+                .first_level_dep
+                .keys()
+                .map(String::capacity)
+                .sum::<usize>();
+        let dependents = self.dependents.capacity()
+            * (size_of::<String>() + size_of::<Vec<String>>())
+            + self
+                .dependents
+                .iter()
+                .map(|(key, list)| key.capacity() + strings(list))
+                .sum::<usize>();
+        tasks + first_level_dep + dependents
+    }
+
+    /// Tasks with a duration of 0, sorted. There is no milestone flag, so every such task
+    /// is reported; most of the time it is an estimate someone forgot to fill in
+    pub fn zero_duration_tasks(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .tasks
+            .values()
+            .filter(|task| task.duration == 0)
+            .map(|task| task.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Time the single-threaded schedule spends in each topological level.
+    /// A whole level runs back to back on the single machine, so this is the sum of its durations
+    pub fn level_durations(&self) -> Result<Vec<u32>, ScheduleError> {
+        Ok(self
+            .schedule_levels()?
+            .iter()
+            .map(|level| level.iter().map(|name| self.tasks[name].duration).sum())
+            .collect())
+    }
+
+    /// Longest task of each topological level, as `(level, task, duration)`; ties go to the
+    /// smaller name.
+    ///
+    /// Run level by level on parallel workers, a level takes as long as its longest task, so
+    /// this is what holds each level up and `duration` is the level's own makespan.
+    /// On the single machine a level takes the sum of its tasks (see `level_durations`),
+    /// and cutting the longest one only saves what is cut off it
+    pub fn level_bottlenecks(&self) -> Result<Vec<(u32, String, u32)>, ScheduleError> {
+        Ok(self
+            .schedule_levels()?
+            .into_iter()
+            .zip(0..)
+            .filter_map(|(mut level, index)| {
+                level.sort();
+                let name = level
+                    .into_iter()
+                    .min_by_key(|name| std::cmp::Reverse(self.tasks[name].duration))?;
+                let duration = self.tasks[&name].duration;
+                Some((index, name, duration))
+            })
+            .collect())
+    }
+
+    /// Sum of how late every task with a deadline finishes (`finish - deadline`, or 0 if on time)
+    /// in the default single-threaded schedule. Saturates at `u32::MAX`
+    pub fn total_tardiness(&self) -> Result<u32, ScheduleError> {
+        Ok(self.tardiness()?.fold(0, u32::saturating_add))
+    }
+
+    /// Lateness of the task that misses its deadline by the most, 0 if all are on time
+    pub fn max_tardiness(&self) -> Result<u32, ScheduleError> {
+        Ok(self.tardiness()?.max().unwrap_or(0))
+    }
+
+    /// Cost of the default single-threaded schedule: `penalty_per_unit` for every unit of time
+    /// a task finishes past its soft deadline. Tasks without one cost nothing
+    pub fn total_penalty(&self) -> Result<u64, ScheduleError> {
+        Ok(self
+            .schedule_tasks()?
+            .into_iter()
+            .filter_map(|(name, start, duration)| {
+                let task = &self.tasks[&name];
+                let late =
+                    (start as u64 + duration as u64).saturating_sub(task.soft_deadline? as u64);
+                Some(task.penalty_per_unit as u64 * late)
+            })
+            .sum())
+    }
+
+    /// Tardiness of every task with a deadline in the default single-threaded schedule
+    fn tardiness(&self) -> Result<impl Iterator<Item = u32> + '_, ScheduleError> {
+        Ok(self
+            .schedule_tasks()?
+            .into_iter()
+            .filter_map(|(name, start, duration)| {
+                let deadline = self.tasks[&name].deadline?;
+                Some(start.saturating_add(duration).saturating_sub(deadline))
+            }))
+    }
+
+    /// Whether the single-threaded makespan is now longer than `baseline_makespan`,
+    /// for failing CI when a change makes the schedule worse
+    pub fn regressed_against(&self, baseline_makespan: u32) -> Result<bool, ScheduleError> {
+        Ok(self.compare_makespan(baseline_makespan)?.1 > 0)
+    }
+
+    /// Current single-threaded makespan and how much longer (positive) or shorter (negative)
+    /// it is than `baseline`
+    pub fn compare_makespan(&self, baseline: u32) -> Result<(u32, i64), ScheduleError> {
+        let makespan = makespan_of(&self.schedule_tasks()?);
+        Ok((makespan, makespan as i64 - baseline as i64))
+    }
+
+    /// Project float against `deadline`: how long before it the single-threaded schedule
+    /// is done, negative if it finishes after it
+    pub fn deadline_buffer(&self, deadline: u32) -> Result<i64, ScheduleError> {
+        Ok(-self.compare_makespan(deadline)?.1)
+    }
+
+    /// Elapsed lifetime of every task in the single-threaded schedule: from the start of the
+    /// earliest task it (transitively) depends on to its own finish.
+    /// A root's span is its duration, a task that waits on upstream work spans that wait too
+    pub fn task_spans(&self) -> Result<HashMap<String, u32>, ScheduleError> {
+        // the schedule is in dependency order, so every dependency is seen before its dependents
+        let mut first_start: HashMap<String, u32> = HashMap::new();
+        let mut spans = HashMap::new();
+        for (name, start, duration) in self.schedule_tasks()? {
+            let first = self.tasks[&name]
+                .dependencies
+                .iter()
+                .filter_map(|dep| first_start.get(dep).copied())
+                .fold(start, u32::min);
+            spans.insert(name.clone(), start + duration - first);
+            first_start.insert(name, first);
+        }
+        Ok(spans)
+    }
+
+    /// How long the machine sat idle right before each task of `schedule_tasks` took it,
+    /// setup included. Nothing is ever idle waiting for unrelated work on one machine,
+    /// so every gap is down to the task it precedes: a pinned start or a lag after one of
+    /// its dependencies. Tasks that followed straight on map to 0
+    pub fn idle_attribution(&self) -> Result<HashMap<String, u32>, ScheduleError> {
+        let mut free_at = 0;
+        let mut idle = HashMap::new();
+        for (name, start, duration) in self.schedule_tasks()? {
+            let task = &self.tasks[&name];
+            idle.insert(name, start - task.setup - free_at);
+            free_at = start + duration + task.teardown;
+        }
+        Ok(idle)
+    }
+
+    /// Sum of `weight * finish` over the default single-threaded schedule
+    pub fn weighted_completion_time(&self) -> Result<u64, ScheduleError> {
+        Ok(self.weighted_completion_time_of(&self.schedule_tasks()?))
+    }
+
+    /// Sum of `weight * finish` over the given `(name, start, duration)` schedule,
+    /// useful for comparing schedules produced with different orderings
+    pub fn weighted_completion_time_of(&self, schedule: &[(String, u32, u32)]) -> u64 {
+        schedule
+            .iter()
+            .map(|(name, start, duration)| {
+                let weight = self.tasks.get(name).map_or(1, |task| task.weight);
+                weight as u64 * (*start as u64 + *duration as u64)
+            })
+            .sum()
+    }
+}
+
+#[test]
+fn test_total_and_average_duration() {
+    let scheduler = crate::example_scheduler();
+    assert_eq!(scheduler.total_duration(), 10);
+    assert_eq!(scheduler.average_duration(), Some(2.5));
+
+    assert_eq!(TaskScheduler::new().average_duration(), None);
+}
+
+#[test]
+fn test_total_duration_does_not_overflow() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], u32::MAX);
+    scheduler.add_task("B", vec![], u32::MAX);
+    assert_eq!(scheduler.total_duration(), 2 * u32::MAX as u64);
+}
+
+#[test]
+fn test_weighted_completion_time() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("R", vec![], 0);
+    scheduler.add_task("X", vec!["R"], 5);
+    scheduler.add_task("Y", vec!["R"], 1);
+
+    // default order runs X first: finishes at 5 and 6
+    assert_eq!(scheduler.weighted_completion_time(), Ok(11));
+
+    // shortest first runs Y first: finishes at 1 and 6
+    let shortest_first = scheduler
+        .schedule_tasks_by(|a, b| a.duration.cmp(&b.duration))
+        .unwrap();
+    assert_eq!(scheduler.weighted_completion_time_of(&shortest_first), 7);
+
+    // weights scale each task's finish time
+    scheduler.set_weight("X", 3).unwrap();
+    assert_eq!(scheduler.weighted_completion_time(), Ok(21));
+    assert_eq!(
+        scheduler.set_weight("Z", 1),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
+
+#[test]
+fn test_bottleneck() {
+    // B, C and D all wait on A: 2 + 1 + 4
+    assert_eq!(
+        crate::example_scheduler().bottleneck(),
+        Ok(Some(("A".to_string(), 7)))
+    );
+    assert_eq!(TaskScheduler::new().bottleneck(), Ok(None));
+}
+
+#[test]
+fn test_level_durations() {
+    // A; B + C; D
+    assert_eq!(
+        crate::example_scheduler().level_durations(),
+        Ok(vec![3, 3, 4])
+    );
+}
+
+#[test]
+fn test_level_bottlenecks() {
+    let mut scheduler = crate::example_scheduler();
+    assert_eq!(
+        scheduler.level_bottlenecks(),
+        Ok(vec![
+            (0, "A".to_string(), 3),
+            (1, "B".to_string(), 2),
+            (2, "D".to_string(), 4),
+        ])
+    );
+    scheduler.set_duration("C", 2).unwrap();
+    assert_eq!(
+        scheduler.level_bottlenecks().unwrap()[1],
+        (1, "B".to_string(), 2)
+    );
+}
+
+#[test]
+fn test_zero_duration_tasks() {
+    let mut scheduler = crate::example_scheduler();
+    assert!(scheduler.zero_duration_tasks().is_empty());
+
+    scheduler.add_task("forgotten", vec!["D"], 0);
+    assert_eq!(scheduler.zero_duration_tasks(), vec!["forgotten"]);
+}
+
+#[test]
+fn test_memory_footprint() {
+    assert_eq!(TaskScheduler::new().memory_footprint(), 0);
+
+    let mut scheduler = crate::example_scheduler();
+    let before = scheduler.memory_footprint();
+    assert!(before > 0);
+    for i in 0..100 {
+        scheduler.add_task(&format!("extra-{i}"), vec!["D"], 1);
+    }
+    assert!(scheduler.memory_footprint() > before);
+}
+
+#[test]
+fn test_tardiness() {
+    let mut scheduler = crate::example_scheduler();
+    assert_eq!(scheduler.total_tardiness(), Ok(0));
+    assert_eq!(scheduler.max_tardiness(), Ok(0));
+
+    // C finishes at 6 and D at 10
+    scheduler.set_deadline("A", Some(10)).unwrap();
+    scheduler.set_deadline("C", Some(4)).unwrap();
+    scheduler.set_deadline("D", Some(5)).unwrap();
+    assert_eq!(scheduler.total_tardiness(), Ok(2 + 5));
+    assert_eq!(scheduler.max_tardiness(), Ok(5));
+}
+
+#[test]
+fn test_regressed_against() {
+    let mut scheduler = crate::example_scheduler();
+    assert_eq!(scheduler.compare_makespan(12), Ok((10, -2)));
+    assert_eq!(scheduler.regressed_against(10), Ok(false));
+
+    scheduler.set_duration("C", 3).unwrap();
+    assert_eq!(scheduler.compare_makespan(10), Ok((12, 2)));
+    assert_eq!(scheduler.regressed_against(10), Ok(true));
+}
+
+#[test]
+fn test_task_spans() {
+    let spans = crate::example_scheduler().task_spans().unwrap();
+    assert_eq!(spans["A"], 3);
+    // B waits for A from 0, C also waits behind B on the single machine
+    assert_eq!(spans["B"], 5);
+    assert_eq!(spans["C"], 6);
+    // D covers the whole timeline, from A starting to its own finish
+    assert_eq!(spans["D"], 10);
+}
+
+#[test]
+fn test_total_penalty() {
+    let mut scheduler = crate::example_scheduler();
+    // a penalty without a soft deadline never applies
+    scheduler.set_soft_deadline("A", None, 100).unwrap();
+    assert_eq!(scheduler.total_penalty(), Ok(0));
+
+    // D finishes at 10, 3 units late
+    scheduler.set_soft_deadline("D", Some(7), 5).unwrap();
+    // B finishes at 5, on time
+    scheduler.set_soft_deadline("B", Some(5), 50).unwrap();
+    assert_eq!(scheduler.total_penalty(), Ok(15));
+}
+
+#[test]
+fn test_idle_attribution() {
+    let mut scheduler = crate::example_scheduler();
+    let idle = scheduler.idle_attribution().unwrap();
+    assert!(idle.values().all(|&gap| gap == 0));
+    assert_eq!(idle.len(), 4);
+
+    // C is done at 6, D now waits until 8 and the gap is its doing
+    scheduler.set_fixed_start("D", Some(8)).unwrap();
+    let idle = scheduler.idle_attribution().unwrap();
+    assert_eq!(idle["D"], 2);
+    assert_eq!(idle.values().sum::<u32>(), 2);
+}
+
+#[test]
+fn test_deadline_buffer() {
+    let scheduler = crate::example_scheduler();
+    // the makespan is 10
+    assert_eq!(scheduler.deadline_buffer(14), Ok(4));
+    assert_eq!(scheduler.deadline_buffer(10), Ok(0));
+    assert_eq!(scheduler.deadline_buffer(7), Ok(-3));
+}
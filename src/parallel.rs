@@ -0,0 +1,1450 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+
+use crate::{alternatives::OrGroups, sort_by_start, ScheduleError, ScheduledTask, TaskScheduler};
+
+/// Finish time of the last task in a schedule
+pub(crate) fn makespan_of(schedule: &[(String, u32, u32)]) -> u32 {
+    schedule
+        .iter()
+        .map(|(_, start, duration)| start + duration)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Which of the eligible ready tasks an idle worker takes, see `parallel_schedule`
+#[derive(Clone, Copy)]
+enum ReadyOrder<'a> {
+    /// The one that has been ready the longest
+    Fifo,
+    /// One continuing the affinity of an idle worker, see `schedule_work_stealing_affinity`
+    Affinity,
+    /// The one with the highest priority, then the one ready the longest
+    Priority(&'a HashMap<String, u32>),
+}
+
+/// Put every task of a parallel schedule on a worker: the idle one that has been busy the
+/// least so far, lowest id first, as `(task, worker, start, end)`
+fn least_busy_workers(
+    workers: usize,
+    schedule: Vec<(String, u32, u32)>,
+) -> Vec<(String, usize, u32, u32)> {
+    let workers = workers.max(1);
+    let mut free_at = vec![0u32; workers];
+    let mut busy = vec![0u32; workers];
+
+    let mut assigned = Vec::new();
+    for (name, start, duration) in schedule {
+        let worker = (0..workers)
+            .filter(|&w| free_at[w] <= start)
+            .min_by_key(|&w| (busy[w], w))
+            .expect("the parallel schedule never runs more tasks than there are workers");
+        free_at[worker] = start + duration;
+        busy[worker] += duration;
+        assigned.push((name, worker, start, start + duration));
+    }
+    assigned
+}
+
+/// Reshape `schedule_work_stealing` output into one timeline per worker,
+/// each holding `(task, start, end)` sorted by start
+pub fn per_worker_timeline(
+    assignments: &[(String, usize, u32, u32)],
+) -> HashMap<usize, Vec<(String, u32, u32)>> {
+    let mut timelines: HashMap<usize, Vec<(String, u32, u32)>> = HashMap::new();
+    for (name, worker, start, end) in assignments {
+        timelines
+            .entry(*worker)
+            .or_default()
+            .push((name.clone(), *start, *end));
+    }
+    for timeline in timelines.values_mut() {
+        timeline.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+    }
+    timelines
+}
+
+impl TaskScheduler {
+    /// Schedule the tasks over `workers` identical workers.
+    ///
+    /// Unlike `schedule_tasks`, a ready task does not wait for an unrelated
+    /// task to finish as long as some worker is idle.
+    /// Output is `(name, start, duration)` in the order tasks were started.
+    /// Tasks sharing an exclusion group never overlap,
+    /// running tasks never hold more of a resource than its capacity,
+    /// and no more than `max_concurrent` tasks of a concurrency group run at once.
+    /// A pinned task that can't start exactly at its fixed time is a `ConstraintViolation`.
+    /// A worker count of 0 is treated as a single worker.
+    pub fn schedule_tasks_parallel(
+        &self,
+        workers: usize,
+    ) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
+        self.parallel_schedule(workers, false, None, ReadyOrder::Fifo, 0)
+    }
+
+    /// Like `schedule_tasks_parallel`, but a divisible task is split over every worker idle
+    /// when it starts, so it takes `duration / workers` (rounded up) of wall-clock time.
+    /// The reported duration is that wall-clock time
+    pub fn schedule_parallel_divisible(
+        &self,
+        workers: usize,
+    ) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
+        self.parallel_schedule(workers, true, None, ReadyOrder::Fifo, 0)
+    }
+
+    /// Like `schedule_tasks_parallel`, but a worker is cold until it gets its first task:
+    /// that task starts `ramp_up` after being handed out, like a serverless cold start.
+    /// An idle worker that already ran something is always used before a cold one,
+    /// so more workers only help if the extra parallelism makes up for their ramp-up
+    pub fn schedule_tasks_parallel_with_ramp_up(
+        &self,
+        workers: usize,
+        ramp_up: u32,
+    ) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
+        self.parallel_schedule(workers, false, None, ReadyOrder::Fifo, ramp_up)
+    }
+
+    /// Schedule with unlimited workers, but never more than `max_in_flight_duration` of
+    /// summed duration running at once. A ready task waits while starting it would go over;
+    /// a task longer than the whole cap still runs, alone. Ordered by start time
+    pub fn schedule_work_capped(
+        &self,
+        max_in_flight_duration: u32,
+    ) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let mut schedule: Vec<ScheduledTask> = self
+            .parallel_schedule(
+                self.tasks.len(),
+                false,
+                Some(max_in_flight_duration),
+                ReadyOrder::Fifo,
+                0,
+            )?
+            .into_iter()
+            .map(ScheduledTask::from)
+            .collect();
+        sort_by_start(&mut schedule);
+        Ok(schedule)
+    }
+
+    /// Event-driven core of the parallel schedulers; `split` spreads divisible tasks
+    /// over the idle workers, `budget` caps the summed duration of running tasks,
+    /// `pick` chooses among the ready tasks, `ramp_up` delays the first task of every worker
+    fn parallel_schedule(
+        &self,
+        workers: usize,
+        split: bool,
+        budget: Option<u32>,
+        pick: ReadyOrder,
+        ramp_up: u32,
+    ) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
+        self.check_resource_requirements()?;
+        let affinity = matches!(pick, ReadyOrder::Affinity);
+
+        let mut in_degree = self.ready_degrees();
+        let mut or_groups = OrGroups::new(&self.tasks);
+        let mut order: Vec<(String, u32, u32)> = Vec::new();
+        let mut idle = workers.max(1);
+        let mut time: u32 = 0;
+        let mut in_use: HashMap<String, u32> = HashMap::new();
+
+        // earliest time a task may start, pushed out by dependency lags and pinned start times
+        let mut earliest: HashMap<String, u32> = self
+            .tasks
+            .values()
+            .filter_map(|task| Some((task.name.clone(), task.fixed_start?)))
+            .collect();
+
+        // seed with the tasks that are free to run right away
+        // sorted, so that the result does not depend on hashmap order
+        let mut roots: Vec<&String> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(name, _)| name)
+            .collect();
+        roots.sort();
+        let mut ready: VecDeque<String> = roots.into_iter().cloned().collect();
+
+        // tasks currently occupying a worker, keyed by the time they finish
+        let mut running: BinaryHeap<Reverse<(u32, String)>> = BinaryHeap::new();
+        // how many workers each running task holds
+        let mut held: HashMap<String, usize> = HashMap::new();
+        // affinity of the last task of every idle worker, `None` for one that hasn't run any
+        let mut freed: Vec<Option<String>> = vec![None; idle];
+        // idle workers that never ran a task yet
+        let mut cold = idle;
+
+        loop {
+            // hand out ready tasks while there is someone to run them,
+            // skipping over the ones still waiting out a lag
+            // or whose exclusion group is held by a running task
+            // or whose concurrency group is already at its limit
+            // or that would go over the in-flight budget
+            while idle > 0 {
+                let at = |name: &String| earliest.get(name).copied().unwrap_or(0);
+                let excluded = |name: &String| {
+                    let groups = &self.tasks[name].exclusion_groups;
+                    running.iter().any(|Reverse((_, other))| {
+                        self.tasks[other]
+                            .exclusion_groups
+                            .iter()
+                            .any(|group| groups.contains(group))
+                    })
+                };
+                let saturated = |name: &String| {
+                    let task = &self.tasks[name];
+                    let (Some(group), Some(limit)) = (&task.concurrency_group, task.max_concurrent)
+                    else {
+                        return false;
+                    };
+                    let instances = running
+                        .iter()
+                        .filter(|Reverse((_, other))| {
+                            self.tasks[other].concurrency_group.as_ref() == Some(group)
+                        })
+                        .count();
+                    instances >= limit as usize
+                };
+                let over_budget = |name: &String| {
+                    let Some(budget) = budget else {
+                        return false;
+                    };
+                    let in_flight: u64 = running
+                        .iter()
+                        .map(|Reverse((_, other))| self.tasks[other].duration as u64)
+                        .sum();
+                    !running.is_empty()
+                        && in_flight + self.tasks[name].duration as u64 > budget as u64
+                };
+                let eligible = |name: &String| {
+                    at(name) <= time
+                        && !excluded(name)
+                        && !saturated(name)
+                        && !over_budget(name)
+                        && self.resources_fit(name, &in_use)
+                };
+                // continuing an idle worker's affinity beats starting one nobody is on,
+                // which beats joining one another worker is already on
+                let rank = |name: &String| match &self.tasks[name].affinity {
+                    Some(group) if freed.contains(&Some(group.clone())) => 0,
+                    Some(group)
+                        if running.iter().any(|Reverse((_, other))| {
+                            self.tasks[other].affinity.as_ref() == Some(group)
+                        }) =>
+                    {
+                        2
+                    }
+                    _ => 1,
+                };
+                let index = match pick {
+                    ReadyOrder::Fifo => ready.iter().position(eligible),
+                    ReadyOrder::Affinity => (0..ready.len())
+                        .filter(|&index| eligible(&ready[index]))
+                        .min_by_key(|&index| (rank(&ready[index]), index)),
+                    ReadyOrder::Priority(priority) => (0..ready.len())
+                        .filter(|&index| eligible(&ready[index]))
+                        .min_by_key(|&index| {
+                            (Reverse(priority.get(&ready[index]).copied()), index)
+                        }),
+                };
+                let Some(index) = index else {
+                    break;
+                };
+                let task_name = ready.remove(index).expect("index is in bounds");
+                if affinity {
+                    let slot = freed
+                        .iter()
+                        .position(|last| *last == self.tasks[&task_name].affinity)
+                        .or_else(|| freed.iter().position(Option::is_none))
+                        .unwrap_or(0);
+                    freed.swap_remove(slot);
+                }
+                let Some(task) = self.tasks.get(&task_name) else {
+                    continue;
+                };
+                let taken = if split && task.divisible { idle } else { 1 };
+                // warm workers first, the task waits for any cold one it needs
+                let warmed = taken.saturating_sub(idle - cold);
+                cold -= warmed;
+                let start = match warmed {
+                    0 => time,
+                    _ => time
+                        .checked_add(ramp_up)
+                        .ok_or(ScheduleError::DurationOverflow)?,
+                };
+                if task.fixed_start.is_some_and(|fixed| fixed < start) {
+                    return Err(ScheduleError::ConstraintViolation(task_name));
+                }
+                for (resource, amount) in &task.resources {
+                    *in_use.entry(resource.clone()).or_insert(0) += amount;
+                }
+                let duration = task.duration.div_ceil(taken as u32);
+                let finish = start
+                    .checked_add(duration)
+                    .ok_or(ScheduleError::DurationOverflow)?;
+                order.push((task_name.clone(), start, duration));
+                running.push(Reverse((finish, task_name.clone())));
+                held.insert(task_name, taken);
+                idle -= taken;
+            }
+
+            // next thing that can change the picture:
+            // a task finishing, or (if a worker is idle) a lag running out
+            let next_finish = running.peek().map(|Reverse((finish, _))| *finish);
+            let next_release = ready
+                .iter()
+                .filter_map(|name| earliest.get(name).copied())
+                .filter(|&at| at > time && idle > 0)
+                .min();
+            let Some(next) = next_finish.into_iter().chain(next_release).min() else {
+                // nothing is running and nothing could be started: we are done
+                break;
+            };
+            time = next;
+
+            // free the worker of every task finishing by now
+            while let Some(Reverse((finish, _))) = running.peek() {
+                if *finish > time {
+                    break;
+                }
+                let Some(Reverse((finish, task_name))) = running.pop() else {
+                    break;
+                };
+                idle += held.remove(&task_name).unwrap_or(1);
+                if affinity {
+                    freed.push(self.tasks[&task_name].affinity.clone());
+                }
+                for (resource, amount) in &self.tasks[&task_name].resources {
+                    if let Some(used) = in_use.get_mut(resource) {
+                        *used -= amount;
+                    }
+                }
+                if let Some(neighbors) = self.dependents.get(&task_name) {
+                    for neighbor in neighbors {
+                        if let Some(degree) = in_degree.get_mut(neighbor) {
+                            let lag = self.tasks.get(neighbor).map_or(0, |t| t.lag(&task_name));
+                            let at = earliest.entry(neighbor.clone()).or_insert(0);
+                            let released = finish
+                                .checked_add(lag)
+                                .ok_or(ScheduleError::DurationOverflow)?;
+                            *at = (*at).max(released);
+
+                            *degree -= 1;
+                            if *degree == 0 {
+                                ready.push_back(neighbor.clone());
+                            }
+                        }
+                    }
+                }
+                for waiting in or_groups.finished(&task_name) {
+                    if let Some(degree) = in_degree.get_mut(waiting) {
+                        let at = earliest.entry(waiting.to_string()).or_insert(0);
+                        *at = (*at).max(finish);
+
+                        *degree -= 1;
+                        if *degree == 0 {
+                            ready.push_back(waiting.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        // same reasoning as in `schedule_tasks`:
+        // a task that never reached zero in-degree is part of a cycle
+        if order.len() < self.tasks.len() {
+            return Err(ScheduleError::CycleDetected);
+        }
+        Ok(order)
+    }
+
+    /// Parallel schedule sorted by start time and then by name.
+    /// Concurrent tasks share a start time, so this differs from the hand-out order
+    /// `schedule_tasks_parallel` reports
+    pub fn schedule_parallel_sorted_by_start(
+        &self,
+        workers: usize,
+    ) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let mut schedule: Vec<ScheduledTask> = self
+            .schedule_tasks_parallel(workers)?
+            .into_iter()
+            .map(ScheduledTask::from)
+            .collect();
+        sort_by_start(&mut schedule);
+        Ok(schedule)
+    }
+
+    /// Parallel schedule with explicit workers, as `(task, worker, start, end)`
+    /// in the order tasks were started.
+    ///
+    /// Timing is that of `schedule_tasks_parallel`: an idle worker takes the next ready task
+    /// right away. Among the idle workers, the one that has been busy the least so far takes it
+    /// (ties go to the lowest id), which spreads the load over all workers
+    pub fn schedule_work_stealing(
+        &self,
+        workers: usize,
+    ) -> Result<Vec<(String, usize, u32, u32)>, ScheduleError> {
+        Ok(least_busy_workers(
+            workers,
+            self.schedule_tasks_parallel(workers)?,
+        ))
+    }
+
+    /// Highest Level First with Estimated Times: the parallel schedule where an idle worker
+    /// takes the ready task with the highest b-level, the longest way from its start to the
+    /// end of a leaf (its own duration plus lags and durations along the way).
+    /// Ties go to the task ready the longest. Workers are assigned like in
+    /// `schedule_work_stealing`, and the output has the same `(task, worker, start, end)` shape.
+    ///
+    /// Starting the longest chains first usually beats `schedule_tasks_parallel`, which takes
+    /// ready tasks in the order they became ready, though it is no guarantee
+    pub fn schedule_hlfet(
+        &self,
+        workers: usize,
+    ) -> Result<Vec<(String, usize, u32, u32)>, ScheduleError> {
+        let mut levels: HashMap<String, u32> = HashMap::new();
+        for name in self.topological_order()?.into_iter().rev() {
+            let task = &self.tasks[&name];
+            let after = self
+                .dependents
+                .get(&name)
+                .into_iter()
+                .flatten()
+                .filter_map(|dependent| {
+                    Some(self.tasks[dependent].lag(&name) + levels.get(dependent)?)
+                })
+                .max()
+                .unwrap_or(0);
+            levels.insert(name, task.duration + after);
+        }
+        let workers = workers.max(1);
+        let schedule =
+            self.parallel_schedule(workers, false, None, ReadyOrder::Priority(&levels), 0)?;
+        Ok(least_busy_workers(workers, schedule))
+    }
+
+    /// Whether `assignments`, as `(task, worker, start, end)` like `schedule_work_stealing`
+    /// returns, is a feasible schedule on `workers` workers: every task appears exactly once,
+    /// worker ids are below `workers`, no worker runs two tasks at once (`[start, end)`
+    /// intervals) and no task starts before its dependencies end plus their lags.
+    /// Names that are not tasks are `NoTaskFound`
+    pub fn is_valid_parallel_schedule(
+        &self,
+        assignments: &[(String, usize, u32, u32)],
+        workers: usize,
+    ) -> Result<bool, ScheduleError> {
+        if assignments
+            .iter()
+            .any(|(name, ..)| !self.tasks.contains_key(name))
+        {
+            return Err(ScheduleError::NoTaskFound);
+        }
+        let mut ends: HashMap<&str, u32> = HashMap::new();
+        for (name, worker, start, end) in assignments {
+            if *worker >= workers || end < start || ends.insert(name, *end).is_some() {
+                return Ok(false);
+            }
+        }
+        if ends.len() < self.tasks.len() {
+            return Ok(false);
+        }
+
+        let mut by_worker: Vec<&(String, usize, u32, u32)> = assignments.iter().collect();
+        by_worker.sort_by_key(|&&(_, worker, start, end)| (worker, start, end));
+        let overlapping = by_worker
+            .windows(2)
+            .any(|pair| pair[0].1 == pair[1].1 && pair[1].2 < pair[0].3);
+        if overlapping {
+            return Ok(false);
+        }
+        Ok(assignments.iter().all(|(name, _, start, _)| {
+            let task = &self.tasks[name];
+            task.dependencies.iter().all(|dep| {
+                ends.get(dep.as_str())
+                    .is_none_or(|&end| end as u64 + task.lag(dep) as u64 <= *start as u64)
+            })
+        }))
+    }
+
+    /// `schedule_work_stealing` that keeps each worker on one affinity group as long as it can,
+    /// to save the cost of switching context between groups.
+    ///
+    /// Among the ready tasks, a worker that just went idle prefers one of the affinity it last ran,
+    /// and otherwise one of a group no other worker is on. It is then handed to a worker whose
+    /// last task shares its affinity, falling back to the least busy idle worker as usual.
+    /// Constraints are those of `schedule_tasks_parallel`, only the choice among ready tasks differs
+    pub fn schedule_work_stealing_affinity(
+        &self,
+        workers: usize,
+    ) -> Result<Vec<(String, usize, u32, u32)>, ScheduleError> {
+        let workers = workers.max(1);
+        let mut free_at = vec![0u32; workers];
+        let mut busy = vec![0u32; workers];
+        let mut last: Vec<Option<&String>> = vec![None; workers];
+
+        let mut assigned = Vec::new();
+        for (name, start, duration) in
+            self.parallel_schedule(workers, false, None, ReadyOrder::Affinity, 0)?
+        {
+            let affinity = self.tasks[&name].affinity.as_ref();
+            let worker = (0..workers)
+                .filter(|&w| free_at[w] <= start)
+                .min_by_key(|&w| (affinity.is_none() || last[w] != affinity, busy[w], w))
+                .expect("the parallel schedule never runs more tasks than there are workers");
+            free_at[worker] = start + duration;
+            busy[worker] += duration;
+            last[worker] = affinity;
+            assigned.push((name, worker, start, start + duration));
+        }
+        Ok(assigned)
+    }
+
+    /// Parallel schedule where a higher priority ready task can take over the worker of
+    /// a lower priority running one. The preempted task resumes later with its remaining duration.
+    ///
+    /// Output is `(name, start, end)` segments ordered by start; a task that got preempted
+    /// shows up once per segment it ran in.
+    /// Among equal priorities, already running tasks keep their worker
+    pub fn schedule_parallel_preemptive(
+        &self,
+        workers: usize,
+    ) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
+        let workers = workers.max(1);
+        let mut in_degree = self.ready_degrees();
+        let mut or_groups = OrGroups::new(&self.tasks);
+        let mut remaining: HashMap<String, u32> = self
+            .tasks
+            .iter()
+            .map(|(name, task)| (name.clone(), task.duration))
+            .collect();
+        let mut earliest: HashMap<String, u32> = HashMap::new();
+        let mut segments: Vec<(String, u32, u32)> = Vec::new();
+        let mut completed = 0;
+        let mut time: u32 = 0;
+
+        // ready (possibly partially run) tasks in the order they became ready
+        let mut ready: Vec<String> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(name, _)| name.clone())
+            .collect();
+        ready.sort();
+
+        // tasks that held a worker during the previous step, and since when
+        let mut open: HashMap<String, u32> = HashMap::new();
+
+        loop {
+            // pick who runs from now on
+            let mut candidates: Vec<&String> = ready
+                .iter()
+                .filter(|name| earliest.get(*name).copied().unwrap_or(0) <= time)
+                .collect();
+            candidates.sort_by_key(|name| {
+                (
+                    Reverse(self.tasks[*name].priority),
+                    !open.contains_key(*name),
+                )
+            });
+            let chosen: Vec<String> = candidates.into_iter().take(workers).cloned().collect();
+
+            // close the segments of tasks that got preempted
+            let preempted: Vec<String> = open
+                .keys()
+                .filter(|name| !chosen.contains(name))
+                .cloned()
+                .collect();
+            for name in preempted {
+                let start = open.remove(&name).expect("task is open");
+                if start < time {
+                    segments.push((name, start, time));
+                }
+            }
+            for name in &chosen {
+                open.entry(name.clone()).or_insert(time);
+            }
+
+            // run until the next completion or the next lag running out
+            let next_finish = chosen
+                .iter()
+                .map(|name| {
+                    time.checked_add(remaining[name])
+                        .ok_or(ScheduleError::DurationOverflow)
+                })
+                .collect::<Result<Vec<u32>, _>>()?
+                .into_iter()
+                .min();
+            let next_release = ready
+                .iter()
+                .filter_map(|name| earliest.get(name).copied())
+                .filter(|&at| at > time)
+                .min();
+            let Some(next) = next_finish.into_iter().chain(next_release).min() else {
+                break;
+            };
+
+            let elapsed = next - time;
+            time = next;
+            for name in &chosen {
+                let left = remaining
+                    .get_mut(name)
+                    .expect("every task has a remaining time");
+                *left -= elapsed;
+                if *left > 0 {
+                    continue;
+                }
+
+                // done: close the last segment and release dependents
+                let start = open.remove(name).expect("task is open");
+                segments.push((name.clone(), start, time));
+                ready.retain(|n| n != name);
+                completed += 1;
+                if let Some(neighbors) = self.dependents.get(name) {
+                    for neighbor in neighbors {
+                        if let Some(degree) = in_degree.get_mut(neighbor) {
+                            let lag = self.tasks.get(neighbor).map_or(0, |t| t.lag(name));
+                            let at = earliest.entry(neighbor.clone()).or_insert(0);
+                            let released = time
+                                .checked_add(lag)
+                                .ok_or(ScheduleError::DurationOverflow)?;
+                            *at = (*at).max(released);
+
+                            *degree -= 1;
+                            if *degree == 0 {
+                                ready.push(neighbor.clone());
+                            }
+                        }
+                    }
+                }
+                for waiting in or_groups.finished(name) {
+                    if let Some(degree) = in_degree.get_mut(waiting) {
+                        *degree -= 1;
+                        if *degree == 0 {
+                            ready.push(waiting.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        if completed < self.tasks.len() {
+            return Err(ScheduleError::CycleDetected);
+        }
+        segments.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+        Ok(segments)
+    }
+
+    /// Greedy list schedule over machines of different speeds, as `(task, machine, start, end)`
+    /// in the order tasks were assigned.
+    ///
+    /// The ready task that can start earliest (ties by name) goes to the machine that would
+    /// finish it first, taking `duration / speed` rounded up; ties go to the machine listed first.
+    /// Machines with a non-positive speed are ignored, and if none is left the first task
+    /// is `ResourceUnavailable`. Exclusion groups and resources are not considered.
+    /// A task that would end past `u32::MAX` is a `DurationOverflow`
+    pub fn schedule_heterogeneous(
+        &self,
+        machines: &[(String, f64)],
+    ) -> Result<Vec<(String, String, u32, u32)>, ScheduleError> {
+        let machines: Vec<&(String, f64)> =
+            machines.iter().filter(|(_, speed)| *speed > 0.0).collect();
+        let mut free_at = vec![0u32; machines.len()];
+
+        let mut in_degree = self.ready_degrees();
+        let mut or_groups = OrGroups::new(&self.tasks);
+        let mut earliest: HashMap<String, u32> = HashMap::new();
+        let mut ready: Vec<String> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(name, _)| name.clone())
+            .collect();
+        let mut assigned: Vec<(String, String, u32, u32)> = Vec::new();
+
+        while !ready.is_empty() {
+            let at = |name: &String| earliest.get(name).copied().unwrap_or(0);
+            let index = (0..ready.len())
+                .min_by(|&a, &b| (at(&ready[a]), &ready[a]).cmp(&(at(&ready[b]), &ready[b])))
+                .expect("ready is not empty");
+            let task_name = ready.swap_remove(index);
+            let ready_at = at(&task_name);
+            let duration = self.tasks[&task_name].duration;
+
+            let Some((machine, start, end)) = machines
+                .iter()
+                .enumerate()
+                .map(|(i, (_, speed))| {
+                    let start = free_at[i].max(ready_at);
+                    // a slow enough machine takes longer than `u32::MAX`, the cast saturates
+                    let took = (duration as f64 / speed).ceil() as u64;
+                    (i, start, start as u64 + took)
+                })
+                .min_by_key(|&(i, _, end)| (end, i))
+            else {
+                return Err(ScheduleError::ResourceUnavailable(task_name));
+            };
+            let end = u32::try_from(end).map_err(|_| ScheduleError::DurationOverflow)?;
+            free_at[machine] = end;
+
+            if let Some(neighbors) = self.dependents.get(&task_name) {
+                for neighbor in neighbors {
+                    if let Some(degree) = in_degree.get_mut(neighbor) {
+                        let lag = self.tasks[neighbor].lag(&task_name);
+                        let at = earliest.entry(neighbor.clone()).or_insert(0);
+                        let released = end
+                            .checked_add(lag)
+                            .ok_or(ScheduleError::DurationOverflow)?;
+                        *at = (*at).max(released);
+
+                        *degree -= 1;
+                        if *degree == 0 {
+                            ready.push(neighbor.clone());
+                        }
+                    }
+                }
+            }
+            for waiting in or_groups.finished(&task_name) {
+                if let Some(degree) = in_degree.get_mut(waiting) {
+                    let at = earliest.entry(waiting.to_string()).or_insert(0);
+                    *at = (*at).max(end);
+
+                    *degree -= 1;
+                    if *degree == 0 {
+                        ready.push(waiting.to_string());
+                    }
+                }
+            }
+            assigned.push((task_name, machines[machine].0.clone(), start, end));
+        }
+
+        if assigned.len() < self.tasks.len() {
+            return Err(ScheduleError::CycleDetected);
+        }
+        Ok(assigned)
+    }
+
+    /// Animation frames: each distinct start time of the parallel schedule with as many workers
+    /// as tasks, mapped to the tasks starting then (sorted)
+    pub fn frames(&self) -> Result<BTreeMap<u32, Vec<String>>, ScheduleError> {
+        let mut frames: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+        for (name, start, _) in self.schedule_tasks_parallel(self.tasks.len())? {
+            frames.entry(start).or_default().push(name);
+        }
+        for names in frames.values_mut() {
+            names.sort();
+        }
+        Ok(frames)
+    }
+
+    /// Tasks with nothing to wait for at time 0, sorted: the first batch to hand to an executor.
+    /// That is the roots (no dependencies and no OR-groups), less any pinned to start later.
+    ///
+    /// This is read off the graph, not off a schedule, so it is infallible and says nothing
+    /// about capacity. Where roots compete for an exclusion group or a resource they are all
+    /// listed here, while `schedule_tasks_parallel` (see `frames`) starts only some of them
+    /// at 0 and the executor has to make the same choice.
+    /// In `schedule_tasks` only one task ever runs at 0
+    pub fn immediate_tasks(&self) -> Vec<String> {
+        let mut immediate: Vec<String> = self
+            .ready_degrees()
+            .into_iter()
+            .filter(|&(_, degree)| degree == 0)
+            .filter(|(name, _)| self.tasks[name].fixed_start.unwrap_or(0) == 0)
+            .map(|(name, _)| name)
+            .collect();
+        immediate.sort();
+        immediate
+    }
+
+    /// Completion histogram of the parallel schedule with a worker for every task:
+    /// `(bucket start, tasks finishing in it)` for each `window` wide bucket from 0 up to the
+    /// makespan, empty ones included. A bucket holds the finishes in `(start, start + window]`,
+    /// the first one also those at 0. A window of 0 is treated as 1
+    pub fn throughput(&self, window: u32) -> Result<Vec<(u32, usize)>, ScheduleError> {
+        let window = window.max(1);
+        let schedule = self.schedule_tasks_parallel(self.tasks.len())?;
+        let buckets = makespan_of(&schedule).div_ceil(window).max(1);
+        let mut counts = vec![0; buckets as usize];
+        for (_, start, duration) in &schedule {
+            let finish = start + duration;
+            counts[(finish.saturating_sub(1) / window) as usize] += 1;
+        }
+        Ok((0..).step_by(window as usize).zip(counts).collect())
+    }
+
+    /// Split the tasks over `buckets` executors, level by level (see `schedule_levels`): the
+    /// tasks of a level are independent, so each level is spread on its own with the greedy
+    /// longest processing time first rule. The longest task goes to the bucket with the least
+    /// work in that level, ties to the one with the least work overall, then the lower index.
+    ///
+    /// Every bucket lists its tasks level by level, longest first within a level.
+    /// Always returns `buckets` buckets, some of them possibly empty. 0 buckets is treated as 1
+    pub fn balanced_partition(&self, buckets: usize) -> Result<Vec<Vec<String>>, ScheduleError> {
+        let buckets = buckets.max(1);
+        let mut partition = vec![Vec::new(); buckets];
+        let mut total = vec![0u64; buckets];
+        for mut level in self.schedule_levels()? {
+            level.sort_by(|a, b| {
+                self.tasks[b]
+                    .duration
+                    .cmp(&self.tasks[a].duration)
+                    .then_with(|| a.cmp(b))
+            });
+            let mut load = vec![0u64; buckets];
+            for name in level {
+                let bucket = (0..buckets)
+                    .min_by_key(|&b| (load[b], total[b], b))
+                    .expect("there is at least one bucket");
+                let duration = self.tasks[&name].duration as u64;
+                load[bucket] += duration;
+                total[bucket] += duration;
+                partition[bucket].push(name);
+            }
+        }
+        Ok(partition)
+    }
+
+    /// Parallel makespan for every worker count from 1 to `max_workers`, as `(workers, makespan)`
+    pub fn makespan_curve(&self, max_workers: usize) -> Result<Vec<(usize, u32)>, ScheduleError> {
+        (1..=max_workers)
+            .map(|workers| {
+                let schedule = self.schedule_tasks_parallel(workers)?;
+                Ok((workers, makespan_of(&schedule)))
+            })
+            .collect()
+    }
+
+    /// How well the parallel schedule keeps `workers` workers busy:
+    /// single-threaded makespan / (parallel makespan × workers).
+    /// 1.0 means no worker ever idles, lower means they wait on dependencies.
+    /// A graph with no work at all counts as 1.0. 0 workers is treated as 1
+    pub fn parallel_efficiency(&self, workers: usize) -> Result<f64, ScheduleError> {
+        let workers = workers.max(1);
+        let serial = makespan_of(&self.schedule_tasks()?);
+        let parallel = makespan_of(&self.schedule_tasks_parallel(workers)?);
+        if parallel == 0 {
+            return Ok(1.0);
+        }
+        Ok(serial as f64 / (parallel as f64 * workers as f64))
+    }
+
+    /// How much sooner the parallel schedule finishes with `current_workers + 1` workers than
+    /// with `current_workers`. List scheduling can get slower with more workers, that counts as 0
+    pub fn marginal_worker_benefit(&self, current_workers: usize) -> Result<u32, ScheduleError> {
+        let current_workers = current_workers.max(1);
+        let now = makespan_of(&self.schedule_tasks_parallel(current_workers)?);
+        let more = makespan_of(&self.schedule_tasks_parallel(current_workers + 1)?);
+        Ok(now.saturating_sub(more))
+    }
+
+    /// Fewest workers for which the parallel schedule finishes within `target_makespan`.
+    ///
+    /// Returns `None` when even unlimited workers can't make it,
+    /// i.e the critical path alone is longer than the target.
+    pub fn min_workers_for(&self, target_makespan: u32) -> Result<Option<usize>, ScheduleError> {
+        // with one worker per task nothing ever waits for a free worker,
+        // so this is the best any worker count can do
+        let unlimited = self.tasks.len().max(1);
+        if makespan_of(&self.schedule_tasks_parallel(unlimited)?) > target_makespan {
+            return Ok(None);
+        }
+
+        // list scheduling is not strictly monotonic in the worker count,
+        // so walk up linearly instead of binary searching
+        for workers in 1..unlimited {
+            if makespan_of(&self.schedule_tasks_parallel(workers)?) <= target_makespan {
+                return Ok(Some(workers));
+            }
+        }
+        Ok(Some(unlimited))
+    }
+}
+
+#[test]
+fn test_parallel_schedule() {
+    let scheduler = crate::example_scheduler();
+
+    let schedule = scheduler.schedule_tasks_parallel(2);
+    assert_eq!(
+        schedule,
+        Ok(vec![
+            ("A".to_string(), 0, 3),
+            ("B".to_string(), 3, 2),
+            ("C".to_string(), 3, 1),
+            ("D".to_string(), 5, 4),
+        ])
+    );
+
+    // a single worker behaves like the single-threaded schedule
+    assert_eq!(
+        makespan_of(&scheduler.schedule_tasks_parallel(1).unwrap()),
+        10
+    );
+}
+
+#[test]
+fn test_min_workers_for() {
+    let scheduler = crate::example_scheduler();
+
+    // critical path A -> B -> D is 9 long
+    assert_eq!(scheduler.min_workers_for(8), Ok(None));
+    assert_eq!(scheduler.min_workers_for(9), Ok(Some(2)));
+    assert_eq!(scheduler.min_workers_for(10), Ok(Some(1)));
+}
+
+#[test]
+fn test_parallel_schedule_respects_lag() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler
+        .add_task_with_lags("D", vec![("B", 2), ("C", 0)], 4)
+        .unwrap();
+    scheduler.add_task("A", vec![], 3);
+    scheduler.add_task("B", vec!["A"], 2);
+    scheduler.add_task("C", vec!["A"], 1);
+
+    let schedule = scheduler.schedule_tasks_parallel(2).unwrap();
+    assert_eq!(schedule[3], ("D".to_string(), 7, 4));
+}
+
+#[test]
+fn test_preemptive_schedule() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("Gate", vec![], 1);
+    scheduler.add_task("Long", vec![], 10);
+    scheduler
+        .add_task_with_lags("Urgent", vec![("Gate", 2)], 3)
+        .unwrap();
+    scheduler.set_priority("Gate", 1).unwrap();
+    scheduler.set_priority("Urgent", 5).unwrap();
+
+    // Urgent becomes ready at 3 and takes the only worker away from Long
+    assert_eq!(
+        scheduler.schedule_parallel_preemptive(1),
+        Ok(vec![
+            ("Gate".to_string(), 0, 1),
+            ("Long".to_string(), 1, 3),
+            ("Urgent".to_string(), 3, 6),
+            ("Long".to_string(), 6, 14),
+        ])
+    );
+
+    // with a second worker nobody needs to be preempted
+    let segments = scheduler.schedule_parallel_preemptive(2).unwrap();
+    assert_eq!(segments.iter().filter(|s| s.0 == "Long").count(), 1);
+}
+
+#[test]
+fn test_preemptive_schedule_or_groups_and_overflow() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("slow", vec![], 5);
+    scheduler
+        .add_task_with_or_deps("X", vec![], vec![vec!["slow"]], 1)
+        .unwrap();
+    assert_eq!(
+        scheduler.schedule_parallel_preemptive(2),
+        Ok(vec![("slow".to_string(), 0, 5), ("X".to_string(), 5, 6)])
+    );
+
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 1);
+    scheduler.add_task("B", vec!["A"], u32::MAX);
+    assert_eq!(
+        scheduler.schedule_parallel_preemptive(1),
+        Err(ScheduleError::DurationOverflow)
+    );
+
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 1);
+    scheduler
+        .add_task_with_lags("B", vec![("A", u32::MAX)], 1)
+        .unwrap();
+    assert_eq!(
+        scheduler.schedule_parallel_preemptive(1),
+        Err(ScheduleError::DurationOverflow)
+    );
+}
+
+#[test]
+fn test_parallel_schedule_exclusion_group() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("X", vec![], 2);
+    scheduler.add_task("Y", vec![], 2);
+    scheduler.add_task("Z", vec![], 3);
+    scheduler.add_exclusion_group("file", &["X", "Y"]).unwrap();
+
+    let schedule = scheduler.schedule_tasks_parallel(4).unwrap();
+    let find = |name: &str| schedule.iter().find(|t| t.0 == name).unwrap().clone();
+    let (x, y, z) = (find("X"), find("Y"), find("Z"));
+
+    // X and Y take turns, Z runs alongside
+    assert!(x.1 + x.2 <= y.1 || y.1 + y.2 <= x.1);
+    assert_eq!(z.1, 0);
+    assert_eq!(makespan_of(&schedule), 4);
+}
+
+#[test]
+fn test_schedule_parallel_sorted_by_start() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], 1);
+    scheduler.add_task("Z", vec!["A"], 2);
+    scheduler.add_task("Y", vec!["A"], 1);
+
+    // Z became ready first, but it shares its start time with Y
+    let handed_out: Vec<String> = scheduler
+        .schedule_tasks_parallel(2)
+        .unwrap()
+        .into_iter()
+        .map(|t| t.0)
+        .collect();
+    assert_eq!(handed_out, vec!["A", "Z", "Y"]);
+
+    let sorted: Vec<(String, u32)> = scheduler
+        .schedule_parallel_sorted_by_start(2)
+        .unwrap()
+        .into_iter()
+        .map(|t| (t.name, t.start))
+        .collect();
+    assert_eq!(
+        sorted,
+        vec![
+            ("A".to_string(), 0),
+            ("Y".to_string(), 1),
+            ("Z".to_string(), 1)
+        ]
+    );
+
+    // single-threaded, the two orders coincide
+    assert_eq!(
+        scheduler.schedule_sorted_by_start().unwrap(),
+        scheduler.schedule().unwrap()
+    );
+}
+
+#[test]
+fn test_makespan_curve() {
+    let curve = crate::example_scheduler().makespan_curve(4).unwrap();
+    // a second worker lets B and C overlap, more than that doesn't help
+    assert_eq!(curve, vec![(1, 10), (2, 9), (3, 9), (4, 9)]);
+}
+
+#[test]
+fn test_parallel_schedule_fixed_start() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.set_fixed_start("C", Some(6)).unwrap();
+
+    let schedule = scheduler.schedule_tasks_parallel(2).unwrap();
+    assert!(schedule.contains(&("C".to_string(), 6, 1)));
+    assert!(schedule.contains(&("D".to_string(), 7, 4)));
+
+    scheduler.set_fixed_start("C", Some(1)).unwrap();
+    assert_eq!(
+        scheduler.schedule_tasks_parallel(2),
+        Err(ScheduleError::ConstraintViolation("C".to_string()))
+    );
+}
+
+#[test]
+fn test_parallel_schedule_concurrency_group() {
+    let mut scheduler = TaskScheduler::new();
+    for name in ["fetch-1", "fetch-2", "fetch-3", "fetch-4"] {
+        scheduler.add_task(name, vec![], 2);
+    }
+    scheduler
+        .add_concurrency_group("fetch", 2, &["fetch-1", "fetch-2", "fetch-3", "fetch-4"])
+        .unwrap();
+
+    let order = scheduler.schedule_tasks_parallel(8).unwrap();
+    for (_, start, _) in &order {
+        let running = order
+            .iter()
+            .filter(|(_, other, duration)| other <= start && *start < other + duration)
+            .count();
+        assert!(running <= 2);
+    }
+    assert_eq!(makespan_of(&order), 4);
+
+    assert_eq!(
+        scheduler.add_concurrency_group("fetch", 1, &["fetch-1", "missing"]),
+        Err(ScheduleError::NoTaskFound)
+    );
+}
+
+#[test]
+fn test_schedule_heterogeneous() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("long", vec![], 10);
+    scheduler.add_task("short", vec![], 1);
+    scheduler.add_task("after", vec!["long"], 4);
+    let machines = [("fast".to_string(), 2.0), ("slow".to_string(), 1.0)];
+
+    let assigned = scheduler.schedule_heterogeneous(&machines).unwrap();
+    let lookup = |name: &str| {
+        assigned
+            .iter()
+            .find(|(task, ..)| task == name)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(lookup("long"), ("long".into(), "fast".into(), 0, 5));
+    // the fast machine is busy, the slow one is done sooner
+    assert_eq!(lookup("short"), ("short".into(), "slow".into(), 0, 1));
+    assert_eq!(lookup("after"), ("after".into(), "fast".into(), 5, 7));
+
+    assert_eq!(
+        scheduler.schedule_heterogeneous(&[]),
+        Err(ScheduleError::ResourceUnavailable("long".into()))
+    );
+    assert_eq!(
+        scheduler.schedule_heterogeneous(&[("crawl".to_string(), 1e-9)]),
+        Err(ScheduleError::DurationOverflow)
+    );
+}
+
+#[test]
+fn test_schedule_heterogeneous_or_groups() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("slow", vec![], 5);
+    scheduler
+        .add_task_with_or_deps("X", vec![], vec![vec!["slow"]], 1)
+        .unwrap();
+    let machines = [("m1".to_string(), 1.0), ("m2".to_string(), 1.0)];
+    assert_eq!(
+        scheduler.schedule_heterogeneous(&machines),
+        Ok(vec![
+            ("slow".into(), "m1".into(), 0, 5),
+            ("X".into(), "m1".into(), 5, 6),
+        ])
+    );
+
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec![], u32::MAX);
+    scheduler
+        .add_task_with_lags("B", vec![("A", 1)], 1)
+        .unwrap();
+    assert_eq!(
+        scheduler.schedule_heterogeneous(&machines),
+        Err(ScheduleError::DurationOverflow)
+    );
+}
+
+#[test]
+fn test_schedule_work_stealing() {
+    let mut scheduler = TaskScheduler::new();
+    for name in ["A", "B", "C", "D", "E", "F"] {
+        scheduler.add_task(name, vec![], 2);
+    }
+    scheduler.add_task("G", vec!["A"], 1);
+
+    let assigned = scheduler.schedule_work_stealing(3).unwrap();
+    assert_eq!(assigned.len(), 7);
+    for worker in 0..3 {
+        assert!(assigned.iter().any(|(_, w, ..)| *w == worker));
+    }
+    // no worker ever runs two tasks at once
+    for (i, (_, worker, start, end)) in assigned.iter().enumerate() {
+        for (_, other, other_start, other_end) in &assigned[i + 1..] {
+            assert!(worker != other || end <= other_start || other_end <= start);
+        }
+    }
+    // six tasks of 2 over three workers, then G: nobody idles while work is ready
+    let makespan = assigned.iter().map(|(.., end)| *end).max().unwrap();
+    assert_eq!(makespan, 5);
+}
+
+#[test]
+fn test_parallel_schedule_divisible() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("split", vec![], 4);
+    scheduler.add_task("after", vec!["split"], 3);
+    scheduler.set_divisible("split", true).unwrap();
+
+    assert_eq!(
+        scheduler.schedule_parallel_divisible(2).unwrap(),
+        vec![("split".to_string(), 0, 2), ("after".to_string(), 2, 3)]
+    );
+    // the plain parallel schedule does not split
+    assert_eq!(
+        makespan_of(&scheduler.schedule_tasks_parallel(2).unwrap()),
+        7
+    );
+}
+
+#[test]
+fn test_immediate_tasks() {
+    let mut scheduler = crate::example_scheduler();
+    assert_eq!(scheduler.immediate_tasks(), ["A"]);
+
+    scheduler.add_task("E", vec![], 1);
+    scheduler.add_task("F", vec![], 1);
+    scheduler.set_fixed_start("F", Some(2)).unwrap();
+    scheduler
+        .add_task_with_or_deps("G", vec![], vec![vec!["E"]], 1)
+        .unwrap();
+    assert_eq!(scheduler.immediate_tasks(), ["A", "E"]);
+    // without contention that is what the parallel schedule starts at 0
+    assert_eq!(scheduler.frames().unwrap()[&0], ["A", "E"]);
+
+    // A and E can't run together, both are still free to go
+    scheduler.add_exclusion_group("lock", &["A", "E"]).unwrap();
+    assert_eq!(scheduler.immediate_tasks(), ["A", "E"]);
+    assert_eq!(scheduler.frames().unwrap()[&0].len(), 1);
+}
+
+#[test]
+fn test_throughput() {
+    let scheduler = crate::example_scheduler();
+    // A finishes at 3, C at 4, B at 5 and D at 9
+    assert_eq!(scheduler.throughput(3), Ok(vec![(0, 1), (3, 2), (6, 1)]));
+    assert_eq!(scheduler.throughput(4), Ok(vec![(0, 2), (4, 1), (8, 1)]));
+    assert_eq!(scheduler.throughput(20), Ok(vec![(0, 4)]));
+    assert_eq!(TaskScheduler::new().throughput(3), Ok(vec![(0, 0)]));
+}
+
+#[test]
+fn test_frames() {
+    let frames = crate::example_scheduler().frames().unwrap();
+    assert_eq!(
+        frames.into_iter().collect::<Vec<_>>(),
+        vec![
+            (0, vec!["A".to_string()]),
+            (3, vec!["B".to_string(), "C".to_string()]),
+            (5, vec!["D".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn test_per_worker_timeline() {
+    let assignments = crate::example_scheduler()
+        .schedule_work_stealing(2)
+        .unwrap();
+    let timelines = per_worker_timeline(&assignments);
+    assert_eq!(timelines.len(), 2);
+
+    let mut names: Vec<&String> = timelines
+        .values()
+        .flatten()
+        .map(|(name, ..)| name)
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["A", "B", "C", "D"]);
+    for timeline in timelines.values() {
+        for pair in timeline.windows(2) {
+            assert!(pair[0].2 <= pair[1].1);
+        }
+    }
+}
+
+#[test]
+fn test_schedule_work_capped() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("long-1", vec![], 5);
+    scheduler.add_task("long-2", vec![], 5);
+    scheduler.add_task("short", vec![], 2);
+
+    // 5 + 5 is over the cap, 5 + 2 is not
+    let schedule = scheduler.schedule_work_capped(8).unwrap();
+    let start_of = |name: &str| schedule.iter().find(|t| t.name == name).unwrap().start;
+    assert_eq!(start_of("long-1"), 0);
+    assert_eq!(start_of("short"), 0);
+    assert_eq!(start_of("long-2"), 5);
+
+    // a task bigger than the cap still runs on its own
+    let schedule = scheduler.schedule_work_capped(4).unwrap();
+    assert_eq!(schedule.iter().map(|t| t.finish()).max(), Some(12));
+}
+
+#[test]
+fn test_schedule_work_stealing_affinity() {
+    let mut scheduler = TaskScheduler::new();
+    for name in ["a1", "a2", "a3", "b1", "b2", "b3"] {
+        scheduler.add_task(name, vec![], 1);
+        scheduler.set_affinity(name, Some(&name[..1])).unwrap();
+    }
+    let workers_of = |assigned: &[(String, usize, u32, u32)], group: &str| {
+        let mut workers: Vec<usize> = assigned
+            .iter()
+            .filter(|(name, ..)| name.starts_with(group))
+            .map(|&(_, worker, ..)| worker)
+            .collect();
+        workers.dedup();
+        workers
+    };
+
+    // in name order the a tasks spill over onto the worker running b
+    let plain = scheduler.schedule_work_stealing(2).unwrap();
+    assert_eq!(workers_of(&plain, "a"), vec![0, 1, 0]);
+
+    let clustered = scheduler.schedule_work_stealing_affinity(2).unwrap();
+    assert_eq!(clustered.len(), 6);
+    assert_eq!(workers_of(&clustered, "a"), vec![0]);
+    assert_eq!(workers_of(&clustered, "b"), vec![1]);
+    // clustering doesn't cost any time here
+    assert!(clustered.iter().all(|&(.., end)| end <= 3));
+}
+
+#[test]
+fn test_marginal_worker_benefit() {
+    let scheduler = crate::example_scheduler();
+    // a second worker runs C next to B
+    assert_eq!(scheduler.marginal_worker_benefit(1), Ok(1));
+    // past that the critical path A-B-D decides
+    assert_eq!(scheduler.marginal_worker_benefit(2), Ok(0));
+    assert_eq!(scheduler.marginal_worker_benefit(5), Ok(0));
+}
+
+#[test]
+fn test_parallel_schedule_ramp_up() {
+    let scheduler = crate::example_scheduler();
+    let makespan = |workers, ramp_up| {
+        makespan_of(
+            &scheduler
+                .schedule_tasks_parallel_with_ramp_up(workers, ramp_up)
+                .unwrap(),
+        )
+    };
+    // the first worker pays its ramp-up once, then runs everything else warm
+    assert_eq!(makespan(1, 5), 15);
+    // a second worker only ever gets C, which then finishes long after B
+    assert_eq!(makespan(2, 5), 18);
+    assert_eq!(makespan(2, 0), 9);
+    assert_eq!(
+        scheduler.schedule_tasks_parallel_with_ramp_up(2, 0),
+        scheduler.schedule_tasks_parallel(2)
+    );
+}
+
+#[test]
+fn test_schedule_hlfet() {
+    // the roots come up in name order, but only `c` has a long chain behind it
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("a", vec![], 1);
+    scheduler.add_task("b", vec![], 1);
+    scheduler.add_task("c", vec![], 1);
+    scheduler.add_task("long", vec!["c"], 10);
+
+    let naive = makespan_of(&scheduler.schedule_tasks_parallel(2).unwrap());
+    let hlfet = scheduler.schedule_hlfet(2).unwrap();
+    let makespan = hlfet.iter().map(|&(_, _, _, end)| end).max().unwrap();
+    assert_eq!(naive, 12);
+    assert_eq!(makespan, 11);
+    assert_eq!((hlfet[0].0.as_str(), hlfet[0].2), ("c", 0));
+
+    // on the example graph there is nothing to gain
+    let scheduler = crate::example_scheduler();
+    let hlfet = scheduler.schedule_hlfet(2).unwrap();
+    assert_eq!(hlfet.iter().map(|&(_, _, _, end)| end).max(), Some(9));
+}
+
+#[test]
+fn test_is_valid_parallel_schedule() {
+    let scheduler = crate::example_scheduler();
+    let assignments = scheduler.schedule_work_stealing(2).unwrap();
+    assert_eq!(
+        scheduler.is_valid_parallel_schedule(&assignments, 2),
+        Ok(true)
+    );
+    // one worker too few for it
+    assert_eq!(
+        scheduler.is_valid_parallel_schedule(&assignments, 1),
+        Ok(false)
+    );
+
+    let entry = |name: &str, worker, start, end| (name.to_string(), worker, start, end);
+    // B and C double-book worker 0
+    let double_booked = vec![
+        entry("A", 0, 0, 3),
+        entry("B", 0, 3, 5),
+        entry("C", 0, 4, 5),
+        entry("D", 1, 5, 9),
+    ];
+    assert_eq!(
+        scheduler.is_valid_parallel_schedule(&double_booked, 2),
+        Ok(false)
+    );
+    // D starts before B is done
+    let early = vec![
+        entry("A", 0, 0, 3),
+        entry("B", 0, 3, 5),
+        entry("C", 1, 3, 4),
+        entry("D", 1, 4, 8),
+    ];
+    assert_eq!(scheduler.is_valid_parallel_schedule(&early, 2), Ok(false));
+    assert_eq!(
+        scheduler.is_valid_parallel_schedule(&early[..3], 2),
+        Ok(false)
+    );
+    assert_eq!(
+        scheduler.is_valid_parallel_schedule(&[entry("X", 0, 0, 1)], 2),
+        Err(ScheduleError::NoTaskFound)
+    );
+
+    // an end at the very last instant plus a lag can't be met, and doesn't overflow
+    let mut lagged = TaskScheduler::new();
+    lagged.add_task("A", vec![], 1);
+    lagged.add_task_with_lags("B", vec![("A", 5)], 0).unwrap();
+    let assignments = [
+        ("A".to_string(), 0, u32::MAX - 1, u32::MAX),
+        ("B".to_string(), 1, u32::MAX, u32::MAX),
+    ];
+    assert_eq!(
+        lagged.is_valid_parallel_schedule(&assignments, 2),
+        Ok(false)
+    );
+}
+
+#[test]
+fn test_balanced_partition() {
+    let mut scheduler = TaskScheduler::new();
+    for (name, duration) in [("a", 8), ("b", 5), ("c", 4), ("d", 3), ("e", 3), ("f", 1)] {
+        scheduler.add_task(name, vec![], duration);
+    }
+    scheduler.add_task("Z", vec!["a"], 2);
+
+    let partition = scheduler.balanced_partition(3).unwrap();
+    assert_eq!(
+        partition,
+        [vec!["a", "Z"], vec!["b", "e"], vec!["c", "d", "f"]]
+    );
+    let work = |bucket: &Vec<String>| -> u32 {
+        bucket
+            .iter()
+            .map(|name| scheduler.tasks[name].duration)
+            .sum()
+    };
+    // the first level splits perfectly into 8 + 8 + 8
+    assert_eq!(partition.iter().map(work).collect::<Vec<_>>(), [10, 8, 8]);
+
+    let mut scheduler = TaskScheduler::new();
+    for name in ["p", "q", "r", "s", "t"] {
+        scheduler.add_task(name, vec![], 2);
+    }
+    let loads: Vec<usize> = scheduler
+        .balanced_partition(2)
+        .unwrap()
+        .iter()
+        .map(Vec::len)
+        .collect();
+    assert_eq!(loads, [3, 2]);
+    assert_eq!(scheduler.balanced_partition(0).unwrap().len(), 1);
+}
+
+#[test]
+fn test_parallel_efficiency() {
+    // a chain can only ever keep one worker busy
+    let mut chain = TaskScheduler::new();
+    chain.add_task("t0", vec![], 2);
+    for i in 1..8 {
+        chain.add_task(&format!("t{i}"), vec![&format!("t{}", i - 1)], 2);
+    }
+    assert_eq!(chain.parallel_efficiency(1).unwrap(), 1.0);
+    assert_eq!(chain.parallel_efficiency(4).unwrap(), 0.25);
+
+    let mut wide = TaskScheduler::new();
+    for i in 0..16 {
+        wide.add_task(&format!("w{i}"), vec![], 3);
+    }
+    assert_eq!(wide.parallel_efficiency(4).unwrap(), 1.0);
+    // 16 tasks on 5 workers take 4 rounds, the last one mostly idle
+    let efficiency = wide.parallel_efficiency(5).unwrap();
+    assert!(efficiency > 0.75 && efficiency < 1.0);
+
+    assert_eq!(TaskScheduler::new().parallel_efficiency(3).unwrap(), 1.0);
+}
@@ -0,0 +1,149 @@
+use crate::{AddError, Task, TaskScheduler};
+
+/// One recorded change to a scheduler, see `enable_recording`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Mutation {
+    AddTask(Task),
+    RemoveTask(String),
+    SetDuration {
+        name: String,
+        duration: u32,
+    },
+    /// A batch of `set_durations`, as `(name, duration)` in the order they were given
+    SetDurations(Vec<(String, u32)>),
+    /// Any other change to a task (attributes or dependencies), as its state afterwards
+    UpdateTask(Task),
+    SetResourceCapacity {
+        resource: String,
+        capacity: u32,
+    },
+}
+
+impl TaskScheduler {
+    /// Start recording every mutation made through the scheduler's methods.
+    ///
+    /// The log starts with the current contents, so `replay` rebuilds the whole scheduler
+    /// and not just what changed since. Calling it again restarts the log.
+    /// Edits made directly to the public fields are not seen
+    pub fn enable_recording(&mut self) {
+        let mut tasks: Vec<&Task> = self.tasks.values().collect();
+        tasks.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut log: Vec<Mutation> = tasks.into_iter().cloned().map(Mutation::AddTask).collect();
+
+        let mut capacities: Vec<(&String, &u32)> = self.resource_capacities.iter().collect();
+        capacities.sort();
+        log.extend(capacities.into_iter().map(|(resource, &capacity)| {
+            Mutation::SetResourceCapacity {
+                resource: resource.clone(),
+                capacity,
+            }
+        }));
+        self.recording = Some(log);
+    }
+
+    /// Recorded mutations, oldest first. Empty unless `enable_recording` was called
+    pub fn mutations(&self) -> &[Mutation] {
+        self.recording.as_deref().unwrap_or_default()
+    }
+
+    /// Apply the recorded mutations to a fresh scheduler, giving one equal to this one.
+    /// The new scheduler has no hook and does not record
+    pub fn replay(&self) -> Result<TaskScheduler, AddError> {
+        let mut scheduler = TaskScheduler::new();
+        for mutation in self.mutations() {
+            match mutation {
+                Mutation::AddTask(task) => {
+                    if scheduler.tasks.contains_key(&task.name) {
+                        return Err(AddError::DuplicateTask(task.name.clone()));
+                    }
+                    scheduler.insert_task(task.clone());
+                }
+                Mutation::RemoveTask(name) => {
+                    scheduler.remove_node(name);
+                }
+                Mutation::SetDuration { name, duration } => {
+                    // only fails for unknown tasks, which the original would have refused too
+                    let _ = scheduler.set_duration(name, *duration);
+                }
+                Mutation::SetDurations(updates) => {
+                    let updates: Vec<(&str, u32)> = updates
+                        .iter()
+                        .map(|(name, duration)| (name.as_str(), *duration))
+                        .collect();
+                    let _ = scheduler.set_durations(&updates);
+                }
+                Mutation::UpdateTask(task) => scheduler.replace_task(task.clone()),
+                Mutation::SetResourceCapacity { resource, capacity } => {
+                    scheduler.set_resource_capacity(resource, *capacity);
+                }
+            }
+        }
+        Ok(scheduler)
+    }
+
+    /// Log `mutation` if recording is on. Built lazily so a disabled log costs nothing
+    pub(crate) fn record<F: FnOnce() -> Mutation>(&mut self, mutation: F) {
+        if let Some(log) = &mut self.recording {
+            log.push(mutation());
+        }
+    }
+
+    /// Log the current state of task `name` as an `UpdateTask`
+    pub(crate) fn record_task(&mut self, name: &str) {
+        if let (Some(log), Some(task)) = (&mut self.recording, self.tasks.get(name)) {
+            log.push(Mutation::UpdateTask(task.clone()));
+        }
+    }
+
+    /// Swap in a new version of an existing task, rewiring its edges to the new dependencies
+    pub(crate) fn replace_task(&mut self, task: Task) {
+        let Some(old) = self.tasks.get(&task.name) else {
+            return;
+        };
+        for dep in &old.dependencies {
+            if let Some(dependents) = self.dependents.get_mut(dep) {
+                dependents.retain(|d| *d != task.name);
+                if dependents.is_empty() {
+                    self.dependents.remove(dep);
+                }
+            }
+        }
+        for dep in &task.dependencies {
+            self.dependents
+                .entry(dep.clone())
+                .or_default()
+                .push(task.name.clone());
+        }
+        self.first_level_dep
+            .insert(task.name.clone(), task.dependencies.len());
+        self.tasks.insert(task.name.clone(), task);
+    }
+}
+
+#[test]
+fn test_replay() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.enable_recording();
+
+    scheduler.add_task("E", vec!["D"], 2);
+    scheduler.set_duration("A", 5).unwrap();
+    scheduler.set_weight("B", 3).unwrap();
+    scheduler.set_dependencies("D", vec!["A"]).unwrap();
+    scheduler.add_exclusion_group("io", &["B", "C"]).unwrap();
+    scheduler.set_resource_capacity("cpu", 2);
+    scheduler.remove_task("C").unwrap();
+
+    // 4 tasks from the start, then one per change (two for the exclusion group)
+    assert_eq!(scheduler.mutations().len(), 4 + 8);
+    assert_eq!(
+        scheduler.mutations()[5],
+        Mutation::SetDuration {
+            name: "A".into(),
+            duration: 5
+        }
+    );
+
+    let replayed = scheduler.replay().unwrap();
+    assert_eq!(replayed, scheduler);
+    assert!(replayed.mutations().is_empty());
+}
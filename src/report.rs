@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use crate::{export::json_string, parallel::makespan_of, ScheduleError, TaskScheduler};
+
+/// Everything `analysis_report` gathers about a scheduler in one go
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AnalysisReport {
+    /// Makespan of the single-threaded `schedule_tasks`
+    pub makespan: u32,
+    /// `critical_path`, root first
+    pub critical_path: Vec<String>,
+    /// Makespan with unlimited workers, the length of the critical path
+    pub critical_path_length: u32,
+    /// `slack_analysis`, by name
+    pub slack: BTreeMap<String, u32>,
+    /// `roots`, sorted
+    pub roots: Vec<String>,
+    /// `leaves`, sorted
+    pub leaves: Vec<String>,
+    pub task_count: usize,
+    /// Number of dependency edges, dependencies that were never added included
+    pub dependency_count: usize,
+    /// `total_duration`
+    pub total_duration: u64,
+}
+
+impl AnalysisReport {
+    /// The report as one JSON object, fields named as in the struct
+    pub fn to_json(&self) -> String {
+        let names = |names: &[String]| -> String {
+            let names: Vec<String> = names.iter().map(|name| json_string(name)).collect();
+            format!("[{}]", names.join(","))
+        };
+        let slack: Vec<String> = self
+            .slack
+            .iter()
+            .map(|(name, slack)| format!("{}:{slack}", json_string(name)))
+            .collect();
+        format!(
+            concat!(
+                "{{\"makespan\":{},\"critical_path\":{},\"critical_path_length\":{},",
+                "\"slack\":{{{}}},\"roots\":{},\"leaves\":{},\"task_count\":{},",
+                "\"dependency_count\":{},\"total_duration\":{}}}"
+            ),
+            self.makespan,
+            names(&self.critical_path),
+            self.critical_path_length,
+            slack.join(","),
+            names(&self.roots),
+            names(&self.leaves),
+            self.task_count,
+            self.dependency_count,
+            self.total_duration
+        )
+    }
+}
+
+impl TaskScheduler {
+    /// Makespans, critical path, slack and the basic counts in one struct,
+    /// for dumping everything at once. Fails like `schedule_tasks` and `critical_path` do
+    pub fn analysis_report(&self) -> Result<AnalysisReport, ScheduleError> {
+        let critical_path = self.critical_path()?;
+        let critical_path_length = self
+            .schedule_asap()?
+            .iter()
+            .map(|task| task.finish())
+            .max()
+            .unwrap_or(0);
+        Ok(AnalysisReport {
+            makespan: makespan_of(&self.schedule_tasks()?),
+            critical_path,
+            critical_path_length,
+            slack: self.slack_analysis()?.into_iter().collect(),
+            roots: self.roots(),
+            leaves: self.leaves(),
+            task_count: self.tasks.len(),
+            dependency_count: self
+                .tasks
+                .values()
+                .map(|task| task.dependencies.len())
+                .sum(),
+            total_duration: self.total_duration(),
+        })
+    }
+
+    /// `analysis_report` as JSON, see `AnalysisReport::to_json`
+    pub fn report_json(&self) -> Result<String, ScheduleError> {
+        Ok(self.analysis_report()?.to_json())
+    }
+}
+
+#[test]
+fn test_analysis_report() {
+    let scheduler = crate::example_scheduler();
+    let report = scheduler.analysis_report().unwrap();
+    assert_eq!(report.makespan, 10);
+    assert_eq!(report.critical_path, vec!["A", "B", "D"]);
+    assert_eq!(report.critical_path_length, 9);
+    assert_eq!(report.slack["C"], 1);
+    assert_eq!(report.dependency_count, 4);
+
+    assert_eq!(
+        scheduler.report_json().unwrap(),
+        concat!(
+            r#"{"makespan":10,"critical_path":["A","B","D"],"critical_path_length":9,"#,
+            r#""slack":{"A":0,"B":0,"C":1,"D":0},"roots":["A"],"leaves":["D"],"task_count":4,"#,
+            r#""dependency_count":4,"total_duration":10}"#
+        )
+    );
+}
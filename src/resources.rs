@@ -0,0 +1,393 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    sort_by_start, Mutation, RecomputeReason, ScheduleError, ScheduledTask, TaskScheduler,
+};
+
+impl TaskScheduler {
+    /// Set how much of `resource` is available at any moment
+    pub fn set_resource_capacity(&mut self, resource: &str, capacity: u32) {
+        self.resource_capacities
+            .insert(resource.to_string(), capacity);
+        self.record(|| Mutation::SetResourceCapacity {
+            resource: resource.to_string(),
+            capacity,
+        });
+        self.notify(RecomputeReason::AttributeChanged);
+    }
+
+    /// Declare that `name` holds `amount` of `resource` while it runs
+    pub fn require_resource(
+        &mut self,
+        name: &str,
+        resource: &str,
+        amount: u32,
+    ) -> Result<(), ScheduleError> {
+        let task = self.tasks.get_mut(name).ok_or(ScheduleError::NoTaskFound)?;
+        task.resources.insert(resource.to_string(), amount);
+        self.record_task(name);
+        self.notify(RecomputeReason::AttributeChanged);
+        Ok(())
+    }
+
+    /// Whether `name` can start given the amounts currently `in_use`
+    pub(crate) fn resources_fit(&self, name: &str, in_use: &HashMap<String, u32>) -> bool {
+        self.tasks[name].resources.iter().all(|(resource, amount)| {
+            match self.resource_capacities.get(resource) {
+                Some(capacity) => in_use
+                    .get(resource)
+                    .copied()
+                    .unwrap_or(0)
+                    .checked_add(*amount)
+                    .is_some_and(|used| used <= *capacity),
+                None => true,
+            }
+        })
+    }
+
+    /// Fail upfront for a task that needs more than a resource's whole capacity,
+    /// it would otherwise wait forever
+    pub(crate) fn check_resource_requirements(&self) -> Result<(), ScheduleError> {
+        let mut names: Vec<&String> = self.tasks.keys().collect();
+        names.sort();
+        for name in names {
+            if !self.resources_fit(name, &HashMap::new()) {
+                return Err(ScheduleError::ResourceUnavailable(name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resource-constrained schedule: every task starts as soon as its dependencies are done
+    /// and the resources it needs are free. The worker count is not limited
+    pub fn schedule_with_resources(&self) -> Result<Vec<(String, u32, u32)>, ScheduleError> {
+        self.schedule_tasks_parallel(self.tasks.len())
+    }
+
+    /// Resource-leveled schedule: tasks are shifted later, within their slack, to flatten the
+    /// peak demand of every resource. The makespan stays that of `schedule_asap`.
+    ///
+    /// Tasks are placed one at a time, least slack first among those whose dependencies
+    /// are placed. Each goes at the start within `[earliest, latest]` that keeps the sum of
+    /// its resources' peaks lowest, the earliest such start on ties.
+    /// Capacities are not enforced, this only ever smooths the early-start profile.
+    /// Pinned tasks stay at their start time, and nothing they wait on is shifted past it;
+    /// a pin that can't be kept is a `ConstraintViolation`.
+    /// Like the other slack based analyses, only the `and_deps` of a task with OR-groups
+    /// are looked at. Ordered by start time
+    pub fn schedule_leveled(&self) -> Result<Vec<ScheduledTask>, ScheduleError> {
+        let latest = self.latest_starts()?;
+        let earliest = self.earliest_starts()?;
+        // latest start that keeps both the makespan and every pinned task after it
+        let mut limit: HashMap<&String, u32> = HashMap::new();
+        let order = self.topological_order()?;
+        for name in order.iter().rev() {
+            let task = &self.tasks[name];
+            let bound = match task.fixed_start {
+                Some(fixed) => fixed,
+                None => self
+                    .dependents
+                    .get(name)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|dependent| {
+                        let lag = self.tasks[dependent].lag(name);
+                        Some(limit.get(dependent)?.saturating_sub(lag + task.duration))
+                    })
+                    .fold(latest[name], u32::min),
+            };
+            limit.insert(name, bound);
+        }
+
+        let mut in_degree = self.first_level_dep.clone();
+        let mut ready: Vec<&String> = self
+            .first_level_dep
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(name, _)| name)
+            .collect();
+
+        // (start, end, amount) already placed, per resource
+        let mut usage: HashMap<&String, Vec<(u32, u32, u32)>> = HashMap::new();
+        let mut peak: HashMap<&String, u32> = HashMap::new();
+        let mut finish: HashMap<&String, u32> = HashMap::new();
+        let mut schedule: Vec<ScheduledTask> = Vec::new();
+
+        while !ready.is_empty() {
+            let slack = |name: &String| latest[name] - earliest[name];
+            let index = (0..ready.len())
+                .min_by(|&a, &b| (slack(ready[a]), ready[a]).cmp(&(slack(ready[b]), ready[b])))
+                .expect("ready is not empty");
+            let name = ready.swap_remove(index);
+            let task = &self.tasks[name];
+
+            let ready_at = task
+                .dependencies
+                .iter()
+                .filter_map(|dep| Some(finish.get(dep)? + task.lag(dep)))
+                .max()
+                .unwrap_or(0);
+            let (from, to) = match task.fixed_start {
+                Some(fixed) if fixed < ready_at => {
+                    return Err(ScheduleError::ConstraintViolation(name.clone()));
+                }
+                Some(fixed) => (fixed, fixed),
+                None => (ready_at, limit[name].max(ready_at)),
+            };
+
+            // demand of `resource` at its highest point while running [start, start + duration)
+            let highest = |resource: &String, start: u32| -> u32 {
+                let end = start + task.duration;
+                let placed = usage.get(resource).map(Vec::as_slice).unwrap_or_default();
+                std::iter::once(start)
+                    .chain(
+                        placed
+                            .iter()
+                            .map(|&(s, _, _)| s)
+                            .filter(|&s| s > start && s < end),
+                    )
+                    .map(|at| {
+                        placed
+                            .iter()
+                            .filter(|&&(s, e, _)| s <= at && at < e)
+                            .map(|&(_, _, amount)| amount)
+                            .sum::<u32>()
+                    })
+                    .max()
+                    .unwrap_or(0)
+            };
+            let cost = |start: u32| -> u32 {
+                if task.duration == 0 {
+                    return 0;
+                }
+                task.resources
+                    .iter()
+                    .map(|(resource, amount)| {
+                        let current = peak.get(resource).copied().unwrap_or(0);
+                        current.max(highest(resource, start) + amount)
+                    })
+                    .sum()
+            };
+
+            // the profile only changes where a placed task ends, so those are the starts worth trying
+            let mut candidates: Vec<u32> = vec![from, to];
+            for placed in usage.values() {
+                candidates.extend(
+                    placed
+                        .iter()
+                        .map(|&(_, e, _)| e)
+                        .filter(|&e| e > from && e < to),
+                );
+            }
+            candidates.sort();
+            candidates.dedup();
+            let start = candidates
+                .into_iter()
+                .min_by_key(|&start| (cost(start), start))
+                .expect("there is at least one candidate");
+
+            if task.duration > 0 {
+                let levels: Vec<(&String, u32, u32)> = task
+                    .resources
+                    .iter()
+                    .map(|(resource, &amount)| {
+                        (resource, amount, highest(resource, start) + amount)
+                    })
+                    .collect();
+                for (resource, amount, level) in levels {
+                    let top = peak.entry(resource).or_insert(0);
+                    *top = (*top).max(level);
+                    usage
+                        .entry(resource)
+                        .or_default()
+                        .push((start, start + task.duration, amount));
+                }
+            }
+            finish.insert(name, start + task.duration);
+            schedule.push(ScheduledTask {
+                name: name.clone(),
+                start,
+                duration: task.duration,
+            });
+
+            for neighbor in self.dependents.get(name).into_iter().flatten() {
+                if let Some(degree) = in_degree.get_mut(neighbor) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        ready.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        sort_by_start(&mut schedule);
+        Ok(schedule)
+    }
+
+    /// Usage of every resource over the resource-constrained schedule.
+    ///
+    /// For each resource, lists the `(time, amount_in_use)` points where usage changes,
+    /// in time order. Usage before the first point is 0
+    pub fn resource_utilization(&self) -> Result<HashMap<String, Vec<(u32, u32)>>, ScheduleError> {
+        // net change of usage at each point in time, per resource
+        let mut deltas: HashMap<String, BTreeMap<u32, i64>> = HashMap::new();
+        for resource in self.resource_capacities.keys() {
+            deltas.entry(resource.clone()).or_default();
+        }
+        for (name, start, duration) in self.schedule_with_resources()? {
+            for (resource, &amount) in &self.tasks[&name].resources {
+                let changes = deltas.entry(resource.clone()).or_default();
+                *changes.entry(start).or_insert(0) += amount as i64;
+                *changes.entry(start + duration).or_insert(0) -= amount as i64;
+            }
+        }
+
+        Ok(deltas
+            .into_iter()
+            .map(|(resource, changes)| {
+                let mut level: i64 = 0;
+                let mut points = Vec::new();
+                for (time, delta) in changes {
+                    if delta != 0 {
+                        level += delta;
+                        points.push((time, level as u32));
+                    }
+                }
+                (resource, points)
+            })
+            .collect())
+    }
+}
+
+#[test]
+fn test_resource_utilization() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.set_resource_capacity("gpu", 1);
+    scheduler.require_resource("B", "gpu", 1).unwrap();
+
+    let utilization = scheduler.resource_utilization().unwrap();
+    // B runs from 3 to 5
+    assert_eq!(utilization["gpu"], vec![(3, 1), (5, 0)]);
+}
+
+#[test]
+fn test_resources_serialize_tasks() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.set_resource_capacity("gpu", 1);
+    scheduler.require_resource("B", "gpu", 1).unwrap();
+    scheduler.require_resource("C", "gpu", 1).unwrap();
+
+    // B and C could run side by side, but there is only one gpu
+    let schedule = scheduler.schedule_with_resources().unwrap();
+    assert_eq!(
+        schedule,
+        vec![
+            ("A".to_string(), 0, 3),
+            ("B".to_string(), 3, 2),
+            ("C".to_string(), 5, 1),
+            ("D".to_string(), 6, 4),
+        ]
+    );
+    assert_eq!(
+        scheduler.resource_utilization().unwrap()["gpu"],
+        vec![(3, 1), (6, 0)]
+    );
+
+    scheduler.require_resource("D", "gpu", 2).unwrap();
+    assert_eq!(
+        scheduler.schedule_with_resources(),
+        Err(ScheduleError::ResourceUnavailable("D".to_string()))
+    );
+}
+
+#[test]
+fn test_resources_fit_full_capacity() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("X", vec![], 1);
+    scheduler.add_task("Y", vec![], 1);
+    scheduler.set_resource_capacity("pool", u32::MAX);
+    scheduler.require_resource("X", "pool", u32::MAX).unwrap();
+    scheduler.require_resource("Y", "pool", u32::MAX).unwrap();
+
+    // each takes the whole pool, so they can't overlap
+    let schedule = scheduler.schedule_tasks_parallel(2).unwrap();
+    let starts: Vec<u32> = schedule.iter().map(|(_, start, _)| *start).collect();
+    assert_eq!(starts, [0, 1]);
+}
+
+#[test]
+fn test_schedule_leveled() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("X", vec![], 2);
+    scheduler.add_task("Y", vec![], 2);
+    scheduler.add_task("Z", vec![], 4);
+    scheduler.require_resource("X", "cpu", 1).unwrap();
+    scheduler.require_resource("Y", "cpu", 1).unwrap();
+
+    let peak = |schedule: &[ScheduledTask]| {
+        (0..4)
+            .map(|t| {
+                schedule
+                    .iter()
+                    .filter(|task| task.start <= t && t < task.start + task.duration)
+                    .map(|task| {
+                        scheduler.tasks[&task.name]
+                            .resources
+                            .get("cpu")
+                            .copied()
+                            .unwrap_or(0)
+                    })
+                    .sum::<u32>()
+            })
+            .max()
+            .unwrap()
+    };
+
+    // both cpu tasks start right away in the early schedule
+    assert_eq!(peak(&scheduler.schedule_asap().unwrap()), 2);
+
+    let leveled = scheduler.schedule_leveled().unwrap();
+    assert_eq!(peak(&leveled), 1);
+    // the makespan set by Z is kept
+    assert!(leveled.iter().all(|task| task.start + task.duration <= 4));
+}
+
+#[test]
+fn test_schedule_leveled_keeps_pins() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("X", vec![], 2);
+    scheduler.add_task("Y", vec![], 2);
+    scheduler.add_task("P", vec!["Y"], 1);
+    scheduler.add_task("Z", vec![], 8);
+    scheduler.require_resource("X", "cpu", 1).unwrap();
+    scheduler.require_resource("Y", "cpu", 1).unwrap();
+    scheduler.set_fixed_start("P", Some(2)).unwrap();
+
+    // Y would be moved out of X's way, but P is pinned right after it
+    let leveled = scheduler.schedule_leveled().unwrap();
+    let start_of = |name: &str| leveled.iter().find(|t| t.name == name).unwrap().start;
+    assert_eq!(start_of("Y"), 0);
+    assert_eq!(start_of("P"), 2);
+    assert!(start_of("X") >= 2);
+
+    // the pin also holds back what P waits on transitively
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("Q", vec![], 1);
+    scheduler.add_task("Y", vec!["Q"], 1);
+    scheduler.add_task("P", vec!["Y"], 1);
+    scheduler.add_task("Z", vec![], 10);
+    scheduler.add_task("W", vec![], 1);
+    scheduler.require_resource("Q", "cpu", 1).unwrap();
+    scheduler.require_resource("W", "cpu", 1).unwrap();
+    scheduler.set_fixed_start("P", Some(3)).unwrap();
+    let leveled = scheduler.schedule_leveled().unwrap();
+    let start_of = |name: &str| leveled.iter().find(|t| t.name == name).unwrap().start;
+    assert!(start_of("Q") <= 1);
+    assert_eq!(start_of("P"), 3);
+
+    scheduler.set_fixed_start("P", Some(1)).unwrap();
+    assert_eq!(
+        scheduler.schedule_leveled(),
+        Err(ScheduleError::ConstraintViolation("P".to_string()))
+    );
+}
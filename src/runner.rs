@@ -0,0 +1,129 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{alternatives::OrGroups, ScheduleError, TaskScheduler};
+
+/// Step-by-step driver over the dependency graph.
+/// The caller decides when a task is actually done and reports it back with `complete`,
+/// which is what lets custom execution loops control timing themselves
+#[derive(Debug)]
+pub struct Runner<'a> {
+    scheduler: &'a TaskScheduler,
+    /// remaining unmet dependencies and OR-groups per task
+    in_degree: HashMap<String, usize>,
+    /// which OR-groups a completed task has satisfied
+    or_groups: OrGroups<'a>,
+    /// tasks whose dependencies are all complete, not yet handed out
+    ready: VecDeque<String>,
+    /// tasks already reported as complete
+    completed: HashSet<String>,
+}
+
+impl<'a> Runner<'a> {
+    /// Next task that is free to run, if any.
+    /// `None` does not mean the run is over, other tasks might still be in progress
+    pub fn next_ready(&mut self) -> Option<String> {
+        self.ready.pop_front()
+    }
+
+    /// Mark `name` as done, making its dependents ready once all of their dependencies
+    /// and OR-groups are done. Completing the same task twice has no further effect
+    pub fn complete(&mut self, name: &str) {
+        if !self.scheduler.tasks.contains_key(name) || !self.completed.insert(name.to_string()) {
+            return;
+        }
+
+        let dependents = self.scheduler.dependents.get(name).into_iter().flatten();
+        let satisfied = self.or_groups.finished(name);
+        for neighbor in dependents.map(String::as_str).chain(satisfied) {
+            if let Some(degree) = self.in_degree.get_mut(neighbor) {
+                *degree -= 1;
+                if *degree == 0 {
+                    self.ready.push_back(neighbor.to_string());
+                }
+            }
+        }
+    }
+
+    /// Whether every task has been reported complete
+    pub fn is_finished(&self) -> bool {
+        self.completed.len() == self.scheduler.tasks.len()
+    }
+}
+
+impl TaskScheduler {
+    /// Create a `Runner` over this graph.
+    /// The graph is checked upfront so a runner never gets stuck on a cycle
+    pub fn runner(&self) -> Result<Runner<'_>, ScheduleError> {
+        self.schedule_tasks()?;
+
+        let in_degree = self.ready_degrees();
+        let mut roots: Vec<String> = in_degree
+            .iter()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(name, _)| name.clone())
+            .collect();
+        roots.sort();
+
+        Ok(Runner {
+            scheduler: self,
+            in_degree,
+            or_groups: OrGroups::new(&self.tasks),
+            ready: roots.into(),
+            completed: HashSet::new(),
+        })
+    }
+}
+
+#[test]
+fn test_runner_completes_in_topological_order() {
+    let scheduler = crate::example_scheduler();
+    let mut run = scheduler.runner().unwrap();
+
+    let mut order = Vec::new();
+    while let Some(task) = run.next_ready() {
+        run.complete(&task);
+        order.push(task);
+    }
+    assert!(run.is_finished());
+    assert_eq!(order.len(), 4);
+
+    // every task shows up after all of its dependencies
+    for (index, name) in order.iter().enumerate() {
+        for dep in &scheduler.tasks[name].dependencies {
+            assert!(order[..index].contains(dep));
+        }
+    }
+}
+
+#[test]
+fn test_runner_rejects_cycle() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("A", vec!["B"], 1);
+    scheduler.add_task("B", vec!["A"], 1);
+
+    assert_eq!(
+        scheduler.runner().map(|_| ()),
+        Err(ScheduleError::CycleDetected)
+    );
+}
+
+#[test]
+fn test_runner_waits_for_or_group() {
+    let mut scheduler = TaskScheduler::new();
+    scheduler.add_task("slow", vec![], 5);
+    scheduler.add_task("fast", vec![], 1);
+    scheduler
+        .add_task_with_or_deps("X", vec![], vec![vec!["slow", "fast"]], 1)
+        .unwrap();
+
+    let mut run = scheduler.runner().unwrap();
+    assert_eq!(run.next_ready().as_deref(), Some("fast"));
+    assert_eq!(run.next_ready().as_deref(), Some("slow"));
+    assert_eq!(run.next_ready(), None);
+
+    run.complete("fast");
+    assert_eq!(run.next_ready().as_deref(), Some("X"));
+    // the group is satisfied once, the other member does not release X again
+    run.complete("slow");
+    assert_eq!(run.next_ready(), None);
+}
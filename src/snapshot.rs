@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use crate::{RecomputeReason, Task, TaskScheduler};
+
+/// Checkpoint of a scheduler's graph and capacities, see `snapshot`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SchedulerSnapshot {
+    tasks: HashMap<String, Task>,
+    first_level_dep: HashMap<String, usize>,
+    dependents: HashMap<String, Vec<String>>,
+    resource_capacities: HashMap<String, u32>,
+}
+
+impl TaskScheduler {
+    /// Checkpoint the current tasks, derived maps and resource capacities, e.g for an undo stack.
+    /// The recompute hook and the recording are not part of it
+    pub fn snapshot(&self) -> SchedulerSnapshot {
+        SchedulerSnapshot {
+            tasks: self.tasks.clone(),
+            first_level_dep: self.first_level_dep.clone(),
+            dependents: self.dependents.clone(),
+            resource_capacities: self.resource_capacities.clone(),
+        }
+    }
+
+    /// Go back to the state `snapshot` was taken in. The hook stays and is notified.
+    /// If recording, the log restarts from the restored state, like calling `enable_recording`
+    pub fn restore(&mut self, snapshot: SchedulerSnapshot) {
+        self.tasks = snapshot.tasks;
+        self.first_level_dep = snapshot.first_level_dep;
+        self.dependents = snapshot.dependents;
+        self.resource_capacities = snapshot.resource_capacities;
+        if self.recording.is_some() {
+            self.enable_recording();
+        }
+        self.notify(RecomputeReason::DependenciesChanged);
+    }
+}
+
+#[test]
+fn test_snapshot_restore() {
+    let mut scheduler = crate::example_scheduler();
+    scheduler.set_resource_capacity("gpu", 1);
+    let snapshot = scheduler.snapshot();
+
+    scheduler.add_task("E", vec!["D"], 2);
+    scheduler.set_duration("A", 7).unwrap();
+    scheduler.remove_task("C").unwrap();
+    scheduler.set_resource_capacity("gpu", 4);
+    assert_ne!(scheduler.snapshot(), snapshot);
+
+    scheduler.restore(snapshot.clone());
+    assert_eq!(scheduler.snapshot(), snapshot);
+    let mut expected = crate::example_scheduler();
+    expected.set_resource_capacity("gpu", 1);
+    assert_eq!(scheduler, expected);
+}
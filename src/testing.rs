@@ -0,0 +1,131 @@
+//! Helpers for testing schedules, behind the `testing` feature
+
+use std::fmt;
+
+use crate::ScheduledTask;
+
+/// Every expectation a `ScheduleAssert` found violated, one message each
+#[derive(Debug, Eq, PartialEq)]
+pub struct AssertionFailures(pub Vec<String>);
+
+impl fmt::Display for AssertionFailures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} schedule expectation(s) failed:", self.0.len())?;
+        for failure in &self.0 {
+            write!(f, "\n  {failure}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Chained expectations over a schedule, checked all at once by `check`:
+///
+/// ```ignore
+/// ScheduleAssert::new(&schedule)
+///     .starts_at("B", 3)
+///     .before("B", "D")
+///     .finishes_by("D", 10)
+///     .check()?;
+/// ```
+///
+/// A task missing from the schedule fails every expectation that names it
+pub struct ScheduleAssert<'s> {
+    schedule: &'s [ScheduledTask],
+    failures: Vec<String>,
+}
+
+impl<'s> ScheduleAssert<'s> {
+    pub fn new(schedule: &'s [ScheduledTask]) -> Self {
+        ScheduleAssert {
+            schedule,
+            failures: Vec::new(),
+        }
+    }
+
+    /// `name` starts exactly at `start`
+    pub fn starts_at(mut self, name: &str, start: u32) -> Self {
+        if let Some(task) = self.find(name) {
+            if task.start != start {
+                let failure = format!("`{name}` starts at {}, expected {start}", task.start);
+                self.failures.push(failure);
+            }
+        }
+        self
+    }
+
+    /// `first` is done by the time `then` starts
+    pub fn before(mut self, first: &str, then: &str) -> Self {
+        if let (Some(a), Some(b)) = (self.find(first), self.find(then)) {
+            if a.finish() > b.start {
+                let failure = format!(
+                    "`{first}` finishes at {}, after `{then}` starts at {}",
+                    a.finish(),
+                    b.start
+                );
+                self.failures.push(failure);
+            }
+        }
+        self
+    }
+
+    /// `name` finishes at `by` or earlier
+    pub fn finishes_by(mut self, name: &str, by: u32) -> Self {
+        if let Some(task) = self.find(name) {
+            if task.finish() > by {
+                let failure = format!("`{name}` finishes at {}, expected by {by}", task.finish());
+                self.failures.push(failure);
+            }
+        }
+        self
+    }
+
+    /// Every violated expectation, in the order they were added
+    pub fn check(self) -> Result<(), AssertionFailures> {
+        match self.failures.is_empty() {
+            true => Ok(()),
+            false => Err(AssertionFailures(self.failures)),
+        }
+    }
+
+    /// Entry of `name`, recording a failure if it is not scheduled
+    fn find(&mut self, name: &str) -> Option<ScheduledTask> {
+        let task = self.schedule.iter().find(|task| task.name == name).cloned();
+        if task.is_none() {
+            self.failures
+                .push(format!("`{name}` is not in the schedule"));
+        }
+        task
+    }
+}
+
+#[test]
+fn test_schedule_assert() {
+    let schedule = crate::example_scheduler().schedule().unwrap();
+    let checked = ScheduleAssert::new(&schedule)
+        .starts_at("B", 3)
+        .before("B", "D")
+        .finishes_by("D", 10)
+        .check();
+    assert_eq!(checked, Ok(()));
+}
+
+#[test]
+fn test_schedule_assert_failures() {
+    let schedule = crate::example_scheduler().schedule().unwrap();
+    let failures = ScheduleAssert::new(&schedule)
+        .starts_at("B", 0)
+        .before("D", "A")
+        .finishes_by("D", 10)
+        .finishes_by("X", 1)
+        .check()
+        .unwrap_err();
+    assert_eq!(
+        failures.to_string(),
+        concat!(
+            "3 schedule expectation(s) failed:\n",
+            "  `B` starts at 3, expected 0\n",
+            "  `D` finishes at 10, after `A` starts at 0\n",
+            "  `X` is not in the schedule"
+        )
+    );
+}
@@ -0,0 +1,73 @@
+use crate::{ScheduleError, Task, TaskScheduler};
+
+/// Callbacks invoked while the single-threaded schedule is being computed.
+/// Both methods default to doing nothing, so implementors only override what they need.
+/// `I` is the instant type of the clock the schedule runs on, see `Clock`
+pub trait ScheduleVisitor<I = u32> {
+    /// `task` was placed on the timeline at `start`
+    fn on_task(&mut self, _task: &Task, _start: I) {}
+
+    /// `from` finished, so one of `to`'s dependencies is now satisfied
+    fn on_edge_relaxed(&mut self, _from: &str, _to: &str) {}
+}
+
+/// Visitor for callers that don't need one
+pub(crate) struct NoopVisitor;
+
+impl<I> ScheduleVisitor<I> for NoopVisitor {}
+
+impl<I, V: ScheduleVisitor<I> + ?Sized> ScheduleVisitor<I> for &mut V {
+    fn on_task(&mut self, task: &Task, start: I) {
+        (**self).on_task(task, start)
+    }
+
+    fn on_edge_relaxed(&mut self, from: &str, to: &str) {
+        (**self).on_edge_relaxed(from, to)
+    }
+}
+
+impl TaskScheduler {
+    /// Run the same traversal as `schedule_tasks`, reporting every step to `visitor`.
+    ///
+    /// On a cyclic graph the visitor has already seen the tasks that could be scheduled
+    /// by the time `CycleDetected` is returned
+    pub fn visit_topological<V: ScheduleVisitor>(
+        &self,
+        visitor: &mut V,
+    ) -> Result<(), ScheduleError> {
+        self.schedule_with_picker(|_| 0, visitor).map(|_| ())
+    }
+}
+
+#[test]
+fn test_visit_topological() {
+    #[derive(Default)]
+    struct Recorder {
+        tasks: Vec<(String, u32)>,
+        edges: Vec<(String, String)>,
+    }
+
+    impl ScheduleVisitor for Recorder {
+        fn on_task(&mut self, task: &Task, start: u32) {
+            self.tasks.push((task.name.clone(), start));
+        }
+
+        fn on_edge_relaxed(&mut self, from: &str, to: &str) {
+            self.edges.push((from.to_string(), to.to_string()));
+        }
+    }
+
+    let scheduler = crate::example_scheduler();
+    let mut recorder = Recorder::default();
+    scheduler.visit_topological(&mut recorder).unwrap();
+
+    let order: Vec<String> = recorder
+        .tasks
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect();
+    assert_eq!(order, scheduler.topological_order().unwrap());
+    assert_eq!(recorder.tasks[3], ("D".to_string(), 6));
+    // one relaxation per dependency edge
+    assert_eq!(recorder.edges.len(), 4);
+}